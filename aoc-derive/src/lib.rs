@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, LitStr, PathArguments, Type,
+};
+
+/// Derives `FromStr` from a pattern of literal text and `{}` placeholders,
+/// one placeholder per field in declaration order.
+///
+/// ```ignore
+/// #[derive(FromPattern)]
+/// #[aoc(pattern = "Button A: X+{}, Y+{}")]
+/// struct Button {
+///     x: u64,
+///     y: u64,
+/// }
+/// ```
+///
+/// Each placeholder matches everything up to the first occurrence of the
+/// literal text that follows it (or the rest of the input for a trailing
+/// placeholder) and is parsed with the field type's `FromStr`. Fields marked
+/// `#[aoc(sep = ",")]` are instead split on the separator (on runs of
+/// whitespace if the separator is whitespace), each item is parsed on its own
+/// and the field is built with `TryFrom<Vec<Item>>`. A different conversion
+/// can be given with `#[aoc(sep = ",", with = "NonEmpty::from_vec")]`, where
+/// the function takes the `Vec<Item>` and returns an `Option`.
+///
+/// Line endings in the input are normalized from `\r\n` to `\n` before
+/// matching, so multi-line patterns accept CRLF input.
+///
+/// An error enum named `<Struct>ParseError` is generated alongside the
+/// struct, reporting which literal or field failed and at which column.
+#[proc_macro_derive(FromPattern, attributes(aoc))]
+pub fn derive_from_pattern(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder,
+}
+
+/// Splits a pattern into literal text and placeholders.
+fn split_pattern(pattern: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = vec![];
+    let mut rest = pattern;
+    while let Some(i) = rest.find("{}") {
+        if i > 0 {
+            segments.push(Segment::Literal(rest[..i].to_string()));
+        } else if segments.last() == Some(&Segment::Placeholder) {
+            return Err("placeholders must be separated by literal text".to_string());
+        }
+        segments.push(Segment::Placeholder);
+        rest = &rest[i + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest.to_string()));
+    }
+
+    Ok(segments)
+}
+
+/// Reads the `#[aoc(key = "...", ...)]` attributes, allowing only `keys`.
+fn aoc_attrs(attrs: &[syn::Attribute], keys: &[&str]) -> syn::Result<HashMap<String, LitStr>> {
+    let mut values = HashMap::new();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("aoc")) {
+        attr.parse_nested_meta(|meta| match keys.iter().find(|k| meta.path.is_ident(k)) {
+            Some(key) => {
+                values.insert(key.to_string(), meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            }
+            None => Err(meta.error("unsupported aoc attribute")),
+        })?;
+    }
+
+    Ok(values)
+}
+
+/// The `T` in `Vec<T>`, `NonEmpty<T>` and similar.
+fn item_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let PathArguments::AngleBracketed(args) = &path.path.segments.last()?.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(t) => Some(t),
+        _ => None,
+    })
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let error_name = format_ident!("{}ParseError", name);
+
+    let pattern = aoc_attrs(&input.attrs, &["pattern"])?
+        .remove("pattern")
+        .ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "missing #[aoc(pattern = \"...\")] attribute",
+            )
+        })?;
+    let segments =
+        split_pattern(&pattern.value()).map_err(|e| syn::Error::new(pattern.span(), e))?;
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "FromPattern can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "FromPattern requires named fields",
+        ));
+    };
+
+    let placeholder_count = segments
+        .iter()
+        .filter(|s| **s == Segment::Placeholder)
+        .count();
+    if placeholder_count != fields.named.len() {
+        return Err(syn::Error::new(
+            pattern.span(),
+            format!(
+                "pattern has {} placeholders but the struct has {} fields",
+                placeholder_count,
+                fields.named.len()
+            ),
+        ));
+    }
+
+    // Match the literals, recording where each field's text starts and ends
+    let mut matching = vec![];
+    let mut field_iter = fields.named.iter();
+    for (i, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Literal(lit) => matching.push(quote! {
+                if !__s[__pos..].starts_with(#lit) {
+                    return Err(#error_name::ExpectedLiteral {
+                        literal: #lit,
+                        column: __pos + 1,
+                    });
+                }
+                __pos += #lit.len();
+            }),
+            Segment::Placeholder => {
+                let field = field_iter.next().unwrap().ident.as_ref().unwrap();
+                let raw = format_ident!("__raw_{}", field);
+                let start = format_ident!("__start_{}", field);
+                let end = match segments.get(i + 1) {
+                    Some(Segment::Literal(next)) => quote! {
+                        match __s[__pos..].find(#next) {
+                            Some(__offset) => __pos + __offset,
+                            None => {
+                                return Err(#error_name::ExpectedLiteral {
+                                    literal: #next,
+                                    column: __s.len() + 1,
+                                })
+                            }
+                        }
+                    },
+                    _ => quote! { __s.len() },
+                };
+                matching.push(quote! {
+                    let #start = __pos;
+                    __pos = #end;
+                    let #raw = &__s[#start..__pos];
+                });
+            }
+        }
+    }
+
+    let mut parsing = vec![];
+    let mut field_names = vec![];
+    for field in fields.named.iter() {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let field_str = ident.to_string();
+        let raw = format_ident!("__raw_{}", ident);
+        let start = format_ident!("__start_{}", ident);
+        field_names.push(ident);
+
+        let invalid = |value: proc_macro2::TokenStream, column: proc_macro2::TokenStream| {
+            quote! {
+                #error_name::InvalidField {
+                    field: #field_str,
+                    value: #value.to_string(),
+                    column: #column,
+                }
+            }
+        };
+
+        let mut field_attrs = aoc_attrs(&field.attrs, &["sep", "with"])?;
+        match field_attrs.remove("sep") {
+            None => {
+                let err = invalid(quote!(#raw), quote!(#start + 1));
+                parsing.push(quote! {
+                    let #ident = #raw.parse::<#ty>().map_err(|_| #err)?;
+                });
+            }
+            Some(sep) => {
+                let item_ty = item_type(ty).ok_or_else(|| {
+                    syn::Error::new_spanned(ty, "sep fields need a type such as Vec<T>")
+                })?;
+                let split = if sep.value().trim().is_empty() {
+                    quote! { #raw.split_whitespace() }
+                } else {
+                    quote! { #raw.split(#sep).map(str::trim).filter(|__item| !__item.is_empty()) }
+                };
+                let item_err = invalid(
+                    quote!(__item),
+                    quote!(__item.as_ptr() as usize - __s.as_ptr() as usize + 1),
+                );
+                let err = invalid(quote!(#raw), quote!(#start + 1));
+                let convert = match field_attrs.remove("with") {
+                    Some(with) => {
+                        let with = with.parse::<syn::Path>()?;
+                        quote! { #with(__items).ok_or_else(|| #err)? }
+                    }
+                    None => quote! {
+                        <#ty as ::core::convert::TryFrom<::std::vec::Vec<#item_ty>>>::try_from(__items).map_err(|_| #err)?
+                    },
+                };
+                parsing.push(quote! {
+                    let mut __items = ::std::vec::Vec::<#item_ty>::new();
+                    for __item in #split {
+                        __items.push(__item.parse::<#item_ty>().map_err(|_| #item_err)?);
+                    }
+                    let #ident = #convert;
+                });
+            }
+        }
+    }
+
+    Ok(quote! {
+        #[derive(Debug, PartialEq, Eq, Clone)]
+        #vis enum #error_name {
+            /// Literal text from the pattern was not found at `column`.
+            ExpectedLiteral { literal: &'static str, column: usize },
+            /// The text for `field` starting at `column` could not be parsed.
+            InvalidField {
+                field: &'static str,
+                value: String,
+                column: usize,
+            },
+            /// The input continues after the end of the pattern.
+            TrailingInput { column: usize },
+        }
+
+        impl ::std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    Self::ExpectedLiteral { literal, column } => {
+                        write!(f, "expected {:?} at column {}", literal, column)
+                    }
+                    Self::InvalidField { field, value, column } => {
+                        write!(f, "invalid {} {:?} at column {}", field, value, column)
+                    }
+                    Self::TrailingInput { column } => {
+                        write!(f, "unexpected input at column {}", column)
+                    }
+                }
+            }
+        }
+
+        impl ::std::error::Error for #error_name {}
+
+        impl ::std::str::FromStr for #name {
+            type Err = #error_name;
+
+            fn from_str(__s: &str) -> Result<Self, Self::Err> {
+                // Patterns are written with `\n`, so accept CRLF input too.
+                // Columns are then relative to the normalized text.
+                let __normalized;
+                let __s = if __s.contains("\r\n") {
+                    __normalized = __s.replace("\r\n", "\n");
+                    __normalized.as_str()
+                } else {
+                    __s
+                };
+                let mut __pos = 0usize;
+                #(#matching)*
+                if __pos != __s.len() {
+                    return Err(#error_name::TrailingInput { column: __pos + 1 });
+                }
+                #(#parsing)*
+                Ok(#name { #(#field_names),* })
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_pattern() {
+        assert_eq!(
+            vec![
+                Segment::Literal("Button A: X+".to_string()),
+                Segment::Placeholder,
+                Segment::Literal(", Y+".to_string()),
+                Segment::Placeholder,
+            ],
+            split_pattern("Button A: X+{}, Y+{}").unwrap()
+        );
+        assert_eq!(
+            vec![
+                Segment::Placeholder,
+                Segment::Literal(": ".to_string()),
+                Segment::Placeholder
+            ],
+            split_pattern("{}: {}").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_split_pattern_adjacent_placeholders() {
+        assert!(split_pattern("{}{}").is_err());
+    }
+}
@@ -9,13 +9,16 @@ extern crate quickcheck_macros;
 mod utils;
 mod y2024;
 
-use crate::utils::template::Solution;
+use crate::utils::sim;
+use crate::utils::template::{self, Solution};
+use crate::utils::viz::Visualizer;
 use chrono::Datelike;
-use clap;
 use clap::Parser;
 use figment::providers::{Env, Format, Toml};
 use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct Config {
@@ -30,6 +33,22 @@ struct Args {
     year: Option<i32>,
     #[arg(short, long)]
     day: Option<u32>,
+    /// Show intermediate states for solutions that support it
+    #[arg(long)]
+    visualize: bool,
+    /// Milliseconds to wait between visualization frames
+    #[arg(long, default_value_t = 50)]
+    frame_delay: u64,
+    /// Also save each visualization frame as a PNG in this directory, along
+    /// with other visualization output such as day 24's circuit.dot
+    #[arg(long)]
+    frames_dir: Option<PathBuf>,
+    /// Step through the simulation interactively for solutions that support it
+    #[arg(long)]
+    step_through: bool,
+    /// Run every day of the year and show which ones are complete
+    #[arg(long, conflicts_with_all = ["day", "visualize", "step_through"])]
+    all: bool,
 }
 
 fn main() {
@@ -44,13 +63,71 @@ fn main() {
     }
     let now = chrono::Utc::now();
     let year = args.year.unwrap_or(now.year());
+    if args.all {
+        if get_solution(year, 1).is_none() {
+            eprintln!("No solutions for {}", year);
+            std::process::exit(1);
+        }
+        for day in 1..=25 {
+            let Some(solution) = get_solution(year, day) else {
+                println!("Day {:>2}: missing", day);
+                continue;
+            };
+            let input = get_input(year, day, &config);
+            println!("{}", summary(day, &*solution, input));
+        }
+        return;
+    }
     let day = args.day.unwrap_or(now.day());
+    let Some(solution) = get_solution(year, day) else {
+        eprintln!("No solution for {} day {}", year, day);
+        std::process::exit(1);
+    };
     let input = get_input(year, day, &config);
-    let solution = get_solution(year, day);
+    if args.visualize {
+        let mut viz = Visualizer::new(
+            std::io::stdout().is_terminal(),
+            Duration::from_millis(args.frame_delay),
+            args.frames_dir,
+        );
+        match solution.visualize(input.clone(), &mut viz) {
+            Ok(true) => {}
+            Ok(false) => eprintln!("No visualization for {} day {}", year, day),
+            Err(e) => eprintln!("Visualization failed: {}", e),
+        }
+    }
+    if args.step_through {
+        match solution.step_through(input.clone()) {
+            Some(mut dbg) => sim::interact(
+                &mut *dbg,
+                std::io::stdin().lock(),
+                std::io::stdout(),
+                std::io::stdout().is_terminal(),
+            )
+            .unwrap(),
+            None => eprintln!("No simulation for {} day {}", year, day),
+        }
+    }
     println!("Part 1: {}", solution.part_1(input.clone()));
     println!("Part 2: {}", solution.part_2(input.clone()))
 }
 
+/// One line of the `--all` overview with both answers. A day counts as
+/// complete once every puzzle it has is answered.
+fn summary(day: u32, solution: &dyn Solution, input: String) -> String {
+    let part_1 = solution.part_1(input.clone());
+    let part_2 = solution.has_part_2().then(|| solution.part_2(input));
+    let complete =
+        template::is_solved(&part_1) && part_2.as_deref().is_none_or(template::is_solved);
+    format!(
+        "Day {:>2}: {}  part 1: {}  part 2: {}",
+        day,
+        if complete { "complete" } else { "incomplete" },
+        part_1,
+        part_2.as_deref().unwrap_or("-")
+    )
+}
+
 fn get_aoc_download_url(year: i32, day: u32) -> String {
     format!("https://adventofcode.com/{}/day/{}/input", year, day)
 }
@@ -76,8 +153,8 @@ fn get_input(year: i32, day: u32, config: &Config) -> String {
     res
 }
 
-fn get_solution(year: i32, day: u32) -> Box<dyn Solution> {
-    match year {
+fn get_solution(year: i32, day: u32) -> Option<Box<dyn Solution>> {
+    Some(match year {
         2024 => match day {
             1 => Box::new(y2024::day1::Sln::new()),
             2 => Box::new(y2024::day2::Sln::new()),
@@ -104,8 +181,47 @@ fn get_solution(year: i32, day: u32) -> Box<dyn Solution> {
             23 => Box::new(y2024::day23::Sln::new()),
             24 => Box::new(y2024::day24::Sln::new()),
             25 => Box::new(y2024::day25::Sln::new()),
-            _ => unimplemented!(),
+            _ => return None,
         },
-        _ => unimplemented!(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_last_day_is_complete() {
+        let input = "#####\n.####\n.....\n.....\n.....\n.....\n.....\n\n.....\n.....\n.....\n.....\n.....\n#....\n#####";
+        assert_eq!(
+            "Day 25: complete  part 1: 1  part 2: -",
+            summary(25, &y2024::day25::Sln::new(), input.to_string())
+        );
+    }
+
+    #[test]
+    fn test_summary_unsolved_day_is_incomplete() {
+        let input = "#####\n#S#E#\n#####";
+        assert_eq!(
+            "Day 16: incomplete  part 1: Unsolved: No path to the end  part 2: Unsolved: No path to the end",
+            summary(16, &y2024::day16::Sln::new(), input.to_string())
+        );
+    }
+
+    #[test]
+    fn test_get_solution_unsupported_year() {
+        assert!(get_solution(2024, 1).is_some());
+        assert!(get_solution(2026, 1).is_none());
+        assert!(get_solution(2024, 26).is_none());
+    }
+
+    #[test]
+    fn test_summary() {
+        let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3";
+        assert_eq!(
+            "Day  1: complete  part 1: 11  part 2: 31",
+            summary(1, &y2024::day1::Sln::new(), input.to_string())
+        );
     }
 }
@@ -0,0 +1,255 @@
+use std::marker::PhantomData;
+
+use crate::utils::Point;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Fixed-size set of integers in `0..capacity`, one bit each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl BitSet {
+    pub fn new(capacity: usize) -> BitSet {
+        BitSet {
+            words: vec![0; capacity.div_ceil(WORD_BITS)],
+            capacity,
+        }
+    }
+
+    /// Adds `i` to the set. Returns `false` if it was already present.
+    pub fn insert(&mut self, i: usize) -> bool {
+        assert!(i < self.capacity, "{} out of range 0..{}", i, self.capacity);
+        let (word, mask) = (i / WORD_BITS, 1 << (i % WORD_BITS));
+        let absent = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        absent
+    }
+
+    /// Removes `i` from the set. Returns `false` if it was not present.
+    pub fn remove(&mut self, i: usize) -> bool {
+        if !self.contains(i) {
+            return false;
+        }
+        self.words[i / WORD_BITS] &= !(1 << (i % WORD_BITS));
+        true
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        i < self.capacity && self.words[i / WORD_BITS] & (1 << (i % WORD_BITS)) != 0
+    }
+
+    /// Number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Empties the set without giving up its allocation.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Elements in both sets. Both must have the same capacity.
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        self.zip_words(other, |a, b| a & b)
+    }
+
+    /// Elements in `self` but not in `other`. Both must have the same
+    /// capacity.
+    pub fn difference(&self, other: &BitSet) -> BitSet {
+        self.zip_words(other, |a, b| a & !b)
+    }
+
+    fn zip_words(&self, other: &BitSet, f: impl Fn(u64, u64) -> u64) -> BitSet {
+        assert_eq!(self.capacity, other.capacity, "capacities differ");
+        BitSet {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            capacity: self.capacity,
+        }
+    }
+
+    /// Elements of the set in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                Some(i * WORD_BITS + bit)
+            })
+        })
+    }
+}
+
+/// Small set of values that can be numbered `0..COUNT`, such as a direction
+/// enum.
+pub trait Indexed: Copy {
+    const COUNT: usize;
+
+    fn index(self) -> usize;
+}
+
+impl Indexed for () {
+    const COUNT: usize = 1;
+
+    fn index(self) -> usize {
+        0
+    }
+}
+
+/// Set of `(Point, S)` states on a `width` by `height` grid, stored as one
+/// bit per state. Meant for visited tracking in hot loops: `clear` keeps the
+/// allocation so one grid can be reused across many searches.
+#[derive(Debug, Clone)]
+pub struct BitGrid<S: Indexed = ()> {
+    bits: BitSet,
+    width: usize,
+    height: usize,
+    state: PhantomData<S>,
+}
+
+impl<S: Indexed> BitGrid<S> {
+    pub fn new(width: usize, height: usize) -> BitGrid<S> {
+        BitGrid {
+            bits: BitSet::new(width * height * S::COUNT),
+            width,
+            height,
+            state: PhantomData,
+        }
+    }
+
+    pub fn in_bounds(&self, p: Point) -> bool {
+        p.x < self.width && p.y < self.height
+    }
+
+    fn index(&self, p: Point, s: S) -> usize {
+        assert!(self.in_bounds(p), "{:?} outside the grid", p);
+        (p.y * self.width + p.x) * S::COUNT + s.index()
+    }
+
+    /// Marks the state as visited. Returns `false` if it already was.
+    pub fn insert(&mut self, p: Point, s: S) -> bool {
+        let i = self.index(p, s);
+        self.bits.insert(i)
+    }
+
+    pub fn contains(&self, p: Point, s: S) -> bool {
+        self.in_bounds(p) && self.bits.contains(self.index(p, s))
+    }
+
+    pub fn remove(&mut self, p: Point, s: S) -> bool {
+        let i = self.index(p, s);
+        self.bits.remove(i)
+    }
+
+    /// Number of states in the set.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+    use std::collections::BTreeSet;
+
+    #[derive(Clone, Copy)]
+    enum Turn {
+        Left,
+        Right,
+    }
+
+    impl Indexed for Turn {
+        const COUNT: usize = 2;
+
+        fn index(self) -> usize {
+            self as usize
+        }
+    }
+
+    #[test]
+    fn test_bit_grid() {
+        let mut grid: BitGrid<Turn> = BitGrid::new(3, 2);
+        assert!(grid.insert(Point::new(2, 1), Turn::Left));
+        assert!(!grid.insert(Point::new(2, 1), Turn::Left));
+        assert!(grid.contains(Point::new(2, 1), Turn::Left));
+        assert!(!grid.contains(Point::new(2, 1), Turn::Right));
+        assert!(!grid.contains(Point::new(3, 1), Turn::Left));
+        assert!(grid.insert(Point::new(0, 0), Turn::Right));
+        assert_eq!(2, grid.len());
+
+        grid.clear();
+        assert!(grid.is_empty());
+        assert!(grid.insert(Point::new(2, 1), Turn::Left));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bit_grid_out_of_bounds() {
+        let mut grid: BitGrid = BitGrid::new(3, 2);
+        grid.insert(Point::new(0, 2), ());
+    }
+
+    #[quickcheck]
+    fn test_bitset_matches_btreeset(ops: Vec<(bool, u8)>) -> bool {
+        let mut bits = BitSet::new(200);
+        let mut brute = BTreeSet::new();
+        for (insert, i) in ops {
+            let i = i as usize % 200;
+            let (a, b) = if insert {
+                (bits.insert(i), brute.insert(i))
+            } else {
+                (bits.remove(i), brute.remove(&i))
+            };
+            if a != b {
+                return false;
+            }
+        }
+        bits.len() == brute.len() && bits.iter().eq(brute.into_iter())
+    }
+
+    #[quickcheck]
+    fn test_set_operations_match_btreeset(a: Vec<u8>, b: Vec<u8>) -> bool {
+        let to_bits = |v: &[u8]| {
+            let mut bits = BitSet::new(256);
+            for &i in v {
+                bits.insert(i as usize);
+            }
+            bits
+        };
+        let (sa, sb): (BTreeSet<usize>, BTreeSet<usize>) = (
+            a.iter().map(|&i| i as usize).collect(),
+            b.iter().map(|&i| i as usize).collect(),
+        );
+        let (ba, bb) = (to_bits(&a), to_bits(&b));
+        ba.intersection(&bb)
+            .iter()
+            .eq(sa.intersection(&sb).copied())
+            && ba.difference(&bb).iter().eq(sa.difference(&sb).copied())
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    collections::{hash_map, HashMap},
+    hash::Hash,
+};
+
+/// Multiset that counts how many times each key has been added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter<K: Hash + Eq> {
+    counts: HashMap<K, usize>,
+}
+
+impl<K: Hash + Eq> Counter<K> {
+    pub fn new() -> Counter<K> {
+        Counter {
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, key: K) {
+        self.add_n(key, 1);
+    }
+
+    pub fn add_n(&mut self, key: K, n: usize) {
+        *self.counts.entry(key).or_insert(0) += n;
+    }
+
+    /// How many times `key` has been added.
+    pub fn get(&self, key: &K) -> usize {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// Number of distinct keys.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Sum of all counts.
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// The key with the highest count. Ties are broken arbitrarily.
+    pub fn most_common(&self) -> Option<(&K, usize)> {
+        self.counts
+            .iter()
+            .max_by_key(|(_, &n)| n)
+            .map(|(k, &n)| (k, n))
+    }
+
+    /// Keys and counts in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, usize)> {
+        self.counts.iter().map(|(k, &n)| (k, n))
+    }
+}
+
+impl<K: Hash + Eq> Default for Counter<K> {
+    fn default() -> Self {
+        Counter::new()
+    }
+}
+
+impl<K: Hash + Eq> FromIterator<K> for Counter<K> {
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let mut counter = Counter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<K: Hash + Eq> Extend<K> for Counter<K> {
+    fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
+        for key in iter {
+            self.add(key);
+        }
+    }
+}
+
+impl<K: Hash + Eq> IntoIterator for Counter<K> {
+    type Item = (K, usize);
+    type IntoIter = hash_map::IntoIter<K, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let mut counter: Counter<char> = "abracadabra".chars().collect();
+        assert_eq!(5, counter.get(&'a'));
+        assert_eq!(0, counter.get(&'z'));
+        assert_eq!(5, counter.len());
+        assert_eq!(11, counter.total());
+        assert_eq!(Some((&'a', 5)), counter.most_common());
+
+        counter.add_n('z', 7);
+        assert_eq!(Some((&'z', 7)), counter.most_common());
+    }
+
+    #[test]
+    fn test_empty() {
+        let counter: Counter<u8> = Counter::new();
+        assert!(counter.is_empty());
+        assert_eq!(None, counter.most_common());
+    }
+}
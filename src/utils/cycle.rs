@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Finds the cycle reached by repeatedly applying `f` to `x0`, remembering
+/// every state seen. Returns `(mu, lambda)` where `mu` is the index of the
+/// first state on the cycle and `lambda` is the cycle length. Calls `f`
+/// exactly `mu + lambda` times.
+pub fn detect<S, F>(x0: S, f: F) -> (usize, usize)
+where
+    S: Hash + Eq + Clone,
+    F: Fn(&S) -> S,
+{
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut state = x0;
+    let mut i = 0;
+    loop {
+        if let Some(&mu) = seen.get(&state) {
+            return (mu, i - mu);
+        }
+        let next = f(&state);
+        seen.insert(state, i);
+        state = next;
+        i += 1;
+    }
+}
+
+/// Returns the state after applying `step` to `state` `n` times. Stops
+/// stepping as soon as a state repeats and jumps over the remaining whole
+/// cycles, so `n` can be far larger than the number of distinct states.
+pub fn extrapolate<S, F>(state: S, step: F, n: usize) -> S
+where
+    S: Hash + Eq + Clone,
+    F: Fn(&S) -> S,
+{
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut history: Vec<S> = Vec::new();
+    let mut state = state;
+    for i in 0..n {
+        if let Some(&mu) = seen.get(&state) {
+            let lambda = i - mu;
+            return history[mu + (n - mu) % lambda].clone();
+        }
+        seen.insert(state.clone(), i);
+        history.push(state.clone());
+        state = step(&state);
+    }
+
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    /// Builds a sequence with a tail of length `mu` followed by a cycle of
+    /// length `lambda`.
+    fn rho(mu: usize, lambda: usize) -> impl Fn(&usize) -> usize {
+        move |&x| if x + 1 < mu + lambda { x + 1 } else { mu }
+    }
+
+    #[test]
+    fn test_detect_simple() {
+        assert_eq!((3, 5), detect(0, rho(3, 5)));
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        assert_eq!((0, 1), detect(7, |&x: &u32| x));
+    }
+
+    #[test]
+    fn test_extrapolate_huge_n() {
+        // 0 1 2 | 3 4 5 6 7 | 3 4 ...
+        let f = rho(3, 5);
+        assert_eq!(2, extrapolate(0, &f, 2));
+        assert_eq!(
+            3 + (1_000_000_000_000 - 3) % 5,
+            extrapolate(0, &f, 1_000_000_000_000)
+        );
+    }
+
+    #[quickcheck]
+    fn test_detect_finds_rho(mu: u8, lambda: u8) {
+        let (mu, lambda) = (mu as usize, lambda as usize + 1);
+        assert_eq!((mu, lambda), detect(0, rho(mu, lambda)));
+    }
+
+    #[quickcheck]
+    fn test_extrapolate_matches_naive(x0: u16, m: u16, n: u16) {
+        let m = m as u64 % 1000 + 1;
+        let f = |&x: &u64| (x * x + 1) % m;
+        let mut naive = x0 as u64 % m;
+        for _ in 0..n {
+            naive = f(&naive);
+        }
+        assert_eq!(naive, extrapolate(x0 as u64 % m, f, n as usize));
+    }
+}
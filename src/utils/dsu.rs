@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use crate::utils::Point;
+
+/// Union-find over the elements `0..n`, with path compression and union by
+/// size.
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl DisjointSet {
+    pub fn new(n: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// Returns the representative of the set containing `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        let mut x = x;
+        while self.parent[x] != root {
+            let next = self.parent[x];
+            self.parent[x] = root;
+            x = next;
+        }
+
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns `false` if they were
+    /// already the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Size of the set containing `x`.
+    #[cfg(test)]
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// Number of disjoint sets.
+    #[cfg(test)]
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// All sets, each listed in ascending order. The sets are ordered by their
+    /// smallest element.
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut index_of_root = HashMap::new();
+        let mut groups: Vec<Vec<usize>> = vec![];
+        for x in 0..self.len() {
+            let root = self.find(x);
+            let i = *index_of_root.entry(root).or_insert_with(|| {
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+            groups[i].push(x);
+        }
+
+        groups
+    }
+}
+
+/// Splits a grid into regions of orthogonally adjacent cells with equal
+/// values. Regions are ordered by their first cell in reading order. Rows may
+/// have different lengths; a cell past the end of a neighbouring row simply
+/// has no neighbour there.
+pub fn grid_regions<T: PartialEq>(grid: &[Vec<T>]) -> Vec<Vec<Point>> {
+    let cells: Vec<Point> = grid
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| (0..row.len()).map(move |x| Point::new(x, y)))
+        .collect();
+    // Index of the first cell of each row in `cells`
+    let starts: Vec<usize> = grid
+        .iter()
+        .scan(0, |start, row| {
+            let row_start = *start;
+            *start += row.len();
+            Some(row_start)
+        })
+        .collect();
+
+    let mut dsu = DisjointSet::new(cells.len());
+    for (y, row) in grid.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if row.get(x + 1) == Some(cell) {
+                dsu.union(starts[y] + x, starts[y] + x + 1);
+            }
+            if grid.get(y + 1).and_then(|r| r.get(x)) == Some(cell) {
+                dsu.union(starts[y] + x, starts[y + 1] + x);
+            }
+        }
+    }
+
+    dsu.groups()
+        .into_iter()
+        .map(|g| g.into_iter().map(|i| cells[i]).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut dsu = DisjointSet::new(6);
+        assert_eq!(6, dsu.components());
+        assert!(dsu.union(0, 1));
+        assert!(dsu.union(2, 3));
+        assert!(dsu.union(1, 3));
+        assert!(!dsu.union(0, 2));
+        assert!(dsu.same(0, 3));
+        assert!(!dsu.same(0, 4));
+        assert_eq!(4, dsu.size_of(2));
+        assert_eq!(3, dsu.components());
+        assert_eq!(vec![vec![0, 1, 2, 3], vec![4], vec![5]], dsu.groups());
+    }
+
+    #[test]
+    fn test_long_chain_does_not_overflow() {
+        let n = 1_000_000;
+        let mut dsu = DisjointSet::new(n);
+        for i in 1..n {
+            dsu.union(i - 1, i);
+        }
+        assert_eq!(n, dsu.size_of(0));
+        assert_eq!(1, dsu.components());
+    }
+
+    #[test]
+    fn test_grid_regions() {
+        let grid: Vec<Vec<char>> = ["AAB", "ABB", "CCB"]
+            .iter()
+            .map(|r| r.chars().collect())
+            .collect();
+        let regions = grid_regions(&grid);
+        assert_eq!(3, regions.len());
+        assert_eq!(
+            vec![Point::new(0, 0), Point::new(1, 0), Point::new(0, 1)],
+            regions[0]
+        );
+        assert_eq!(4, regions[1].len());
+        assert_eq!(vec![Point::new(0, 2), Point::new(1, 2)], regions[2]);
+    }
+
+    #[test]
+    fn test_grid_regions_ragged_rows() {
+        let grid: Vec<Vec<char>> = ["AAAA", "A", "AB", "BBB"]
+            .iter()
+            .map(|r| r.chars().collect())
+            .collect();
+        let regions = grid_regions(&grid);
+        assert_eq!(
+            vec![
+                Point::new(0, 0),
+                Point::new(1, 0),
+                Point::new(2, 0),
+                Point::new(3, 0),
+                Point::new(0, 1),
+                Point::new(0, 2),
+            ],
+            regions[0]
+        );
+        assert_eq!(
+            vec![
+                Point::new(1, 2),
+                Point::new(0, 3),
+                Point::new(1, 3),
+                Point::new(2, 3)
+            ],
+            regions[1]
+        );
+        assert_eq!(2, regions.len());
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use crate::utils::Point;
+
+/// Number of cells in the region.
+pub fn area(cells: &HashSet<Point>) -> usize {
+    cells.len()
+}
+
+/// Number of unit edges between a cell in the region and one outside it.
+pub fn perimeter(cells: &HashSet<Point>) -> usize {
+    cells
+        .iter()
+        .map(|p| {
+            [p.left(), p.right(), p.up(), p.down()]
+                .iter()
+                .filter(|n| !cells.contains(n))
+                .count()
+        })
+        .sum()
+}
+
+/// Number of straight fence sides around the region, including the sides of
+/// any holes. A polygon has as many sides as corners, so this counts the
+/// convex and concave corners at each cell.
+pub fn sides(cells: &HashSet<Point>) -> usize {
+    let mut corners = 0;
+    for p in cells {
+        for (v, h) in [
+            (p.up(), p.left()),
+            (p.up(), p.right()),
+            (p.down(), p.left()),
+            (p.down(), p.right()),
+        ] {
+            let diagonal = Point::new(h.x, v.y);
+            match (cells.contains(&v), cells.contains(&h)) {
+                (false, false) => corners += 1,
+                (true, true) if !cells.contains(&diagonal) => corners += 1,
+                _ => {}
+            }
+        }
+    }
+
+    corners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    fn region(rows: &[&str]) -> HashSet<Point> {
+        let mut cells = HashSet::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    cells.insert(Point::new(x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    /// Small arbitrary region, offset from the origin so no coordinate wraps.
+    fn small_region(coords: Vec<(u8, u8)>) -> HashSet<Point> {
+        coords
+            .into_iter()
+            .map(|(x, y)| Point::new(x as usize % 6 + 1, y as usize % 6 + 1))
+            .collect()
+    }
+
+    /// Counts sides by collecting fence segments per direction and counting
+    /// maximal runs of consecutive segments.
+    fn brute_force_sides(cells: &HashSet<Point>) -> usize {
+        let mut sides = 0;
+        for dir in 0..4 {
+            // (line, position along line) for each fence segment
+            let mut segments: Vec<(usize, usize)> = cells
+                .iter()
+                .filter_map(|p| match dir {
+                    0 if !cells.contains(&p.up()) => Some((p.y, p.x)),
+                    1 if !cells.contains(&p.down()) => Some((p.y, p.x)),
+                    2 if !cells.contains(&p.left()) => Some((p.x, p.y)),
+                    3 if !cells.contains(&p.right()) => Some((p.x, p.y)),
+                    _ => None,
+                })
+                .collect();
+            segments.sort();
+            for (i, s) in segments.iter().enumerate() {
+                if i == 0 || segments[i - 1] != (s.0, s.1 - 1) {
+                    sides += 1;
+                }
+            }
+        }
+        sides
+    }
+
+    #[test]
+    fn test_square() {
+        let cells = region(&["##", "##"]);
+        assert_eq!(4, area(&cells));
+        assert_eq!(8, perimeter(&cells));
+        assert_eq!(4, sides(&cells));
+    }
+
+    #[test]
+    fn test_ring() {
+        let cells = region(&["#####", "#...#", "#.#.#", "#...#", "#####"]);
+        assert_eq!(17, area(&cells));
+        assert_eq!(12, sides(&cells));
+    }
+
+    #[test]
+    fn test_e_shape() {
+        let cells = region(&["#####", "#....", "#####", "#....", "#####"]);
+        assert_eq!(12, sides(&cells));
+    }
+
+    #[test]
+    fn test_empty() {
+        let cells = HashSet::new();
+        assert_eq!(0, perimeter(&cells));
+        assert_eq!(0, sides(&cells));
+    }
+
+    #[quickcheck]
+    fn test_perimeter_matches_brute_force(coords: Vec<(u8, u8)>) {
+        let cells = small_region(coords);
+        let mut brute = 0;
+        for p in cells.iter() {
+            for n in [p.left(), p.right(), p.up(), p.down()] {
+                if !cells.contains(&n) {
+                    brute += 1;
+                }
+            }
+        }
+        assert_eq!(brute, perimeter(&cells));
+        assert_eq!(cells.len(), area(&cells));
+    }
+
+    #[quickcheck]
+    fn test_sides_matches_brute_force(coords: Vec<(u8, u8)>) {
+        let cells = small_region(coords);
+        assert_eq!(brute_force_sides(&cells), sides(&cells));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::utils::bitset::BitSet;
+
+/// Undirected graph on the nodes `0..n`, stored as one adjacency bitset per
+/// node. Meant for dense graphs with up to a few thousand nodes.
+#[derive(Debug, Clone)]
+pub struct Graph {
+    adjacency: Vec<BitSet>,
+}
+
+impl Graph {
+    pub fn new(n: usize) -> Graph {
+        Graph {
+            adjacency: vec![BitSet::new(n); n],
+        }
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// Connects `a` and `b`. Self-loops are ignored.
+    pub fn add_edge(&mut self, a: usize, b: usize) {
+        if a != b {
+            self.adjacency[a].insert(b);
+            self.adjacency[b].insert(a);
+        }
+    }
+
+    #[cfg(test)]
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.adjacency[a].contains(b)
+    }
+
+    /// Every triangle once, as `(a, b, c)` with `a < b < c`.
+    pub fn triangles(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        (0..self.len()).flat_map(move |a| {
+            self.adjacency[a]
+                .iter()
+                .filter(move |&b| b > a)
+                .flat_map(move |b| {
+                    self.adjacency[a]
+                        .intersection(&self.adjacency[b])
+                        .iter()
+                        .filter(move |&c| c > b)
+                        .map(move |c| (a, b, c))
+                        .collect::<Vec<_>>()
+                })
+        })
+    }
+
+    /// A largest set of nodes that are all connected to each other, in
+    /// ascending order. Found with Bron–Kerbosch, pivoting on the node with
+    /// the most neighbours among the candidates to cut down on branches.
+    pub fn max_clique(&self) -> Vec<usize> {
+        let mut best = vec![];
+        let mut all = BitSet::new(self.len());
+        for i in 0..self.len() {
+            all.insert(i);
+        }
+        self.bron_kerbosch(&mut vec![], all, BitSet::new(self.len()), &mut best);
+
+        best.sort();
+        best
+    }
+
+    /// Extends `clique` with nodes from `candidates`, skipping anything that
+    /// would only rediscover a clique containing a node in `excluded`.
+    fn bron_kerbosch(
+        &self,
+        clique: &mut Vec<usize>,
+        mut candidates: BitSet,
+        mut excluded: BitSet,
+        best: &mut Vec<usize>,
+    ) {
+        if candidates.is_empty() {
+            if excluded.is_empty() && clique.len() > best.len() {
+                *best = clique.clone();
+            }
+            return;
+        }
+        // Even taking every candidate wouldn't beat the best so far
+        if clique.len() + candidates.len() <= best.len() {
+            return;
+        }
+
+        let pivot = candidates
+            .iter()
+            .chain(excluded.iter())
+            .max_by_key(|&u| candidates.intersection(&self.adjacency[u]).len())
+            .unwrap();
+        for v in candidates.difference(&self.adjacency[pivot]).iter() {
+            clique.push(v);
+            self.bron_kerbosch(
+                clique,
+                candidates.intersection(&self.adjacency[v]),
+                excluded.intersection(&self.adjacency[v]),
+                best,
+            );
+            clique.pop();
+            candidates.remove(v);
+            excluded.insert(v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    fn graph(n: usize, edges: &[(usize, usize)]) -> Graph {
+        let mut g = Graph::new(n);
+        for &(a, b) in edges {
+            g.add_edge(a, b);
+        }
+        g
+    }
+
+    #[test]
+    fn test_triangles() {
+        let g = graph(5, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 1), (3, 4)]);
+        assert_eq!(
+            vec![(0, 1, 2), (1, 2, 3)],
+            g.triangles().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_max_clique() {
+        let g = graph(
+            6,
+            &[
+                (0, 1),
+                (1, 2),
+                (0, 2),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (3, 5),
+                (2, 4),
+                (2, 5),
+            ],
+        );
+        assert_eq!(vec![2, 3, 4, 5], g.max_clique());
+        assert_eq!(Vec::<usize>::new(), Graph::new(0).max_clique());
+        assert_eq!(vec![0], Graph::new(3).max_clique());
+    }
+
+    #[quickcheck]
+    fn test_max_clique_matches_brute_force(edges: Vec<(u8, u8)>) -> bool {
+        let n = 8;
+        let edges: Vec<(usize, usize)> = edges
+            .iter()
+            .map(|&(a, b)| (a as usize % n, b as usize % n))
+            .collect();
+        let g = graph(n, &edges);
+
+        let largest = (0u32..1 << n)
+            .filter(|&mask| {
+                let nodes: Vec<usize> = (0..n).filter(|&i| mask & (1 << i) != 0).collect();
+                nodes
+                    .iter()
+                    .all(|&a| nodes.iter().all(|&b| a == b || g.has_edge(a, b)))
+            })
+            .map(|mask| mask.count_ones() as usize)
+            .max()
+            .unwrap();
+
+        let clique = g.max_clique();
+        clique.len() == largest
+            && clique
+                .iter()
+                .all(|&a| clique.iter().all(|&b| a == b || g.has_edge(a, b)))
+    }
+}
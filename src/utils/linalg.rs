@@ -0,0 +1,301 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use crate::utils::num::gcd;
+
+/// Exact fraction, always stored in lowest terms with a positive denominator.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub fn new(num: i128, den: i128) -> Rational {
+        assert!(den != 0, "zero denominator");
+        let g = gcd(num, den) * den.signum();
+        Rational {
+            num: num / g,
+            den: den / g,
+        }
+    }
+
+    pub fn zero() -> Rational {
+        Rational::from(0)
+    }
+
+    pub fn one() -> Rational {
+        Rational::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    /// Returns the value as an integer, or `None` if it is not whole.
+    pub fn to_integer(self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Rational { num: value, den: 1 }
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Rational::zero()
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.den, self.den * rhs.num)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Self::Output {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// Solution set of a linear system `A x = b`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum LinearSolution {
+    /// Exactly one solution.
+    Unique(Vec<Rational>),
+    /// The equations contradict each other.
+    Inconsistent,
+    /// Every `particular + t_1 * basis[0] + t_2 * basis[1] + ...` for
+    /// arbitrary `t_i` is a solution.
+    Parametric {
+        particular: Vec<Rational>,
+        basis: Vec<Vec<Rational>>,
+    },
+}
+
+/// Solves `a x = b` exactly using Gauss-Jordan elimination. `a` is given as
+/// a list of rows, which must all have the same length.
+pub fn solve(a: &[Vec<i128>], b: &[i128]) -> LinearSolution {
+    assert_eq!(a.len(), b.len(), "row count mismatch");
+    let cols = a.first().map_or(0, |r| r.len());
+    let mut m: Vec<Vec<Rational>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, &rhs)| {
+            assert_eq!(cols, row.len(), "ragged matrix");
+            row.iter()
+                .chain([rhs].iter())
+                .map(|&v| Rational::from(v))
+                .collect()
+        })
+        .collect();
+
+    let mut pivot_cols = vec![];
+    let mut r = 0;
+    for c in 0..cols {
+        let Some(pivot) = (r..m.len()).find(|&i| !m[i][c].is_zero()) else {
+            continue;
+        };
+        m.swap(r, pivot);
+        let p = m[r][c];
+        for v in m[r].iter_mut() {
+            *v = *v / p;
+        }
+        let pivot_row = m[r].clone();
+        for (i, row) in m.iter_mut().enumerate() {
+            if i != r && !row[c].is_zero() {
+                let factor = row[c];
+                for (v, &pv) in row.iter_mut().zip(&pivot_row).skip(c) {
+                    *v = *v - factor * pv;
+                }
+            }
+        }
+        pivot_cols.push(c);
+        r += 1;
+    }
+
+    // Any remaining row is all zeros on the left hand side
+    if m[r..].iter().any(|row| !row[cols].is_zero()) {
+        return LinearSolution::Inconsistent;
+    }
+
+    let mut particular = vec![Rational::zero(); cols];
+    for (row, &c) in pivot_cols.iter().enumerate() {
+        particular[c] = m[row][cols];
+    }
+
+    if pivot_cols.len() == cols {
+        return LinearSolution::Unique(particular);
+    }
+
+    let basis = (0..cols)
+        .filter(|c| !pivot_cols.contains(c))
+        .map(|free| {
+            let mut v = vec![Rational::zero(); cols];
+            v[free] = Rational::one();
+            for (row, &c) in pivot_cols.iter().enumerate() {
+                v[c] = -m[row][free];
+            }
+            v
+        })
+        .collect();
+
+    LinearSolution::Parametric { particular, basis }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    fn r(num: i128, den: i128) -> Rational {
+        Rational::new(num, den)
+    }
+
+    fn apply(a: &[Vec<i128>], x: &[Rational]) -> Vec<Rational> {
+        a.iter()
+            .map(|row| {
+                row.iter().zip(x).fold(Rational::zero(), |acc, (&v, &x)| {
+                    acc + Rational::from(v) * x
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rational_normalizes() {
+        assert_eq!(r(1, 2), r(2, 4));
+        assert_eq!(r(-1, 2), r(1, -2));
+        assert_eq!(r(0, 1), r(0, -5));
+        assert_eq!("-3/4", r(6, -8).to_string());
+        assert_eq!("3", r(6, 2).to_string());
+    }
+
+    #[test]
+    fn test_rational_arithmetic() {
+        assert_eq!(r(5, 6), r(1, 2) + r(1, 3));
+        assert_eq!(r(1, 6), r(1, 2) - r(1, 3));
+        assert_eq!(r(1, 6), r(1, 2) * r(1, 3));
+        assert_eq!(r(3, 2), r(1, 2) / r(1, 3));
+        assert!(r(1, 3) < r(1, 2));
+    }
+
+    #[test]
+    fn test_solve_unique() {
+        // Claw machine from the day 13 example
+        let a = vec![vec![94, 22], vec![34, 67]];
+        assert_eq!(
+            LinearSolution::Unique(vec![r(80, 1), r(40, 1)]),
+            solve(&a, &[8400, 5400])
+        );
+    }
+
+    #[test]
+    fn test_solve_inconsistent() {
+        let a = vec![vec![1, 2], vec![2, 4]];
+        assert_eq!(LinearSolution::Inconsistent, solve(&a, &[3, 7]));
+    }
+
+    #[test]
+    fn test_solve_parametric() {
+        let a = vec![vec![1, 2], vec![2, 4]];
+        let LinearSolution::Parametric { particular, basis } = solve(&a, &[3, 6]) else {
+            panic!("expected parametric solution");
+        };
+        assert_eq!(vec![r(3, 1), r(0, 1)], particular);
+        assert_eq!(vec![vec![r(-2, 1), r(1, 1)]], basis);
+    }
+
+    #[quickcheck]
+    fn test_solve_finds_solution(coefficients: Vec<i8>, x: (i8, i8, i8)) {
+        // Build a 3x3 system from arbitrary (possibly singular) coefficients
+        // with a known solution, so it can never be inconsistent.
+        let mut it = coefficients.into_iter().chain(std::iter::repeat(0));
+        let a: Vec<Vec<i128>> = (0..3)
+            .map(|_| (0..3).map(|_| it.next().unwrap() as i128).collect())
+            .collect();
+        let x = [x.0, x.1, x.2].map(|v| Rational::from(v as i128));
+        let b: Vec<i128> = apply(&a, &x)
+            .iter()
+            .map(|v| v.to_integer().unwrap())
+            .collect();
+        let expected: Vec<Rational> = b.iter().map(|&v| Rational::from(v)).collect();
+
+        match solve(&a, &b) {
+            LinearSolution::Unique(sol) => {
+                assert_eq!(x.to_vec(), sol);
+            }
+            LinearSolution::Inconsistent => panic!("system has a solution"),
+            LinearSolution::Parametric { particular, basis } => {
+                assert_eq!(expected, apply(&a, &particular));
+                for v in basis {
+                    assert!(apply(&a, &v).iter().all(|c| c.is_zero()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Cache for pure recursive functions. The closure passed to
+/// [`Memo::get_or_insert_with`] receives the memo itself, so it can recurse
+/// through it:
+///
+/// ```ignore
+/// fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+///     if n < 2 {
+///         return n;
+///     }
+///     memo.get_or_insert_with(n, |memo| fib(memo, n - 1) + fib(memo, n - 2))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Memo<K, V> {
+    pub fn new() -> Memo<K, V> {
+        Memo {
+            cache: HashMap::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.cache.insert(key, value);
+    }
+
+    /// Returns the cached value for `key`, computing it with `f` first if
+    /// needed.
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> V
+    where
+        F: FnOnce(&mut Self) -> V,
+    {
+        if let Some(v) = self.cache.get(&key) {
+            return v.clone();
+        }
+
+        let v = f(self);
+        self.insert(key, v.clone());
+        v
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn fib(memo: &mut Memo<u64, u64>, n: u64, calls: &Cell<usize>) -> u64 {
+        calls.set(calls.get() + 1);
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_insert_with(n, |memo| fib(memo, n - 1, calls) + fib(memo, n - 2, calls))
+    }
+
+    #[test]
+    fn test_recursive_calls_are_cached() {
+        let calls = Cell::new(0);
+        let mut memo = Memo::new();
+        assert_eq!(12586269025, fib(&mut memo, 50, &calls));
+        assert!(calls.get() < 200);
+        assert_eq!(49, memo.len());
+    }
+}
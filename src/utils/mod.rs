@@ -3,11 +3,28 @@ use std::ops::{Add, Sub};
 #[cfg(test)]
 use quickcheck::Arbitrary;
 
+pub(crate) mod bitset;
+pub(crate) mod counter;
+pub(crate) mod cycle;
+pub(crate) mod dsu;
+pub(crate) mod geometry;
+pub(crate) mod graph;
+pub(crate) mod linalg;
+pub(crate) mod memo;
+pub(crate) mod num;
+pub(crate) mod parse;
+pub(crate) mod ranges;
+pub(crate) mod sim;
 pub(crate) mod template;
+pub(crate) mod toposort;
+pub(crate) mod viz;
+pub(crate) mod vm;
+
+pub(crate) use counter::Counter;
 
 type IntType = usize;
 
-#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Hash)]
 pub struct Point {
     pub x: IntType,
     pub y: IntType,
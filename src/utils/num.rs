@@ -0,0 +1,147 @@
+use std::ops::{Neg, Rem};
+
+/// Greatest common divisor of any signed integer type. The result is always
+/// non-negative, and `gcd(0, 0) == 0`.
+pub fn gcd<T>(a: T, b: T) -> T
+where
+    T: Copy + Ord + Default + Rem<Output = T> + Neg<Output = T>,
+{
+    let zero = T::default();
+    let (mut a, mut b) = (a, b);
+    while b != zero {
+        (a, b) = (b, a % b);
+    }
+    if a < zero {
+        -a
+    } else {
+        a
+    }
+}
+
+/// Extended Euclidean algorithm. Returns `(g, x, y)` such that
+/// `a * x + b * y == g` and `g == gcd(a, b)`.
+pub fn egcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Chinese Remainder Theorem. Takes a list of `(residue, modulus)` pairs and
+/// returns `(x, l)` where `l` is the lcm of the moduli and `x` in `0..l` is the
+/// unique solution to `x ≡ residue (mod modulus)` for every pair. The moduli
+/// need not be coprime; `None` is returned if the congruences contradict each
+/// other.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut x: i128 = 0;
+    let mut l: i128 = 1;
+    for &(r, m) in congruences {
+        let (r, m) = (r as i128, m as i128);
+        let (g, p, _) = egcd(l as i64, m as i64);
+        let (g, p) = (g as i128, p as i128);
+        let diff = r - x;
+        if diff % g != 0 {
+            return None;
+        }
+        let m_g = m / g;
+        let k = (diff / g % m_g * p).rem_euclid(m_g);
+        x += l * k;
+        l *= m_g;
+        x = x.rem_euclid(l);
+    }
+    Some((x as i64, l as i64))
+}
+
+/// Number of decimal digits in `n`, where zero has one digit.
+pub fn num_digits(n: u64) -> u32 {
+    n.checked_ilog10().unwrap_or(0) + 1
+}
+
+/// Splits `n` into the digits before and the `k` lowest digits, e.g.
+/// `split_digits(123456, 2) == (1234, 56)`.
+pub fn split_digits(n: u64, k: u32) -> (u64, u64) {
+    let shift = 10u64.pow(k);
+    (n / shift, n % shift)
+}
+
+/// Concatenates the decimal digits of `a` and `b`, e.g.
+/// `concat_digits(12, 345) == Some(12345)`. Returns `None` on overflow.
+pub fn concat_digits(a: u64, b: u64) -> Option<u64> {
+    a.checked_mul(10u64.checked_pow(num_digits(b))?)?
+        .checked_add(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(6, gcd(48, 18));
+        assert_eq!(6, gcd(-48, 18));
+        assert_eq!(5, gcd(0, 5));
+        assert_eq!(0, gcd(0, 0));
+        assert_eq!(6, gcd(48i128, -18));
+        assert_eq!(3, gcd(-9i32, -6));
+    }
+
+    #[test]
+    fn test_crt_coprime() {
+        assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+    }
+
+    #[test]
+    fn test_crt_non_coprime() {
+        assert_eq!(Some((10, 12)), crt(&[(4, 6), (2, 4)]));
+        assert_eq!(None, crt(&[(1, 6), (2, 4)]));
+    }
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(1, num_digits(0));
+        assert_eq!(1, num_digits(9));
+        assert_eq!(2, num_digits(10));
+        assert_eq!(20, num_digits(u64::MAX));
+        assert_eq!((1234, 56), split_digits(123456, 2));
+        assert_eq!((10, 0), split_digits(1000, 2));
+        assert_eq!(Some(12345), concat_digits(12, 345));
+        assert_eq!(Some(100), concat_digits(10, 0));
+        assert_eq!(None, concat_digits(u64::MAX, 1));
+    }
+
+    #[quickcheck]
+    fn test_egcd_bezout(a: i32, b: i32) {
+        let (a, b) = (a as i64, b as i64);
+        let (g, x, y) = egcd(a, b);
+        assert_eq!(gcd(a, b), g);
+        assert_eq!(g, a * x + b * y);
+    }
+
+    #[quickcheck]
+    fn test_crt_solves_congruences(x: u32, m1: u16, m2: u16) {
+        let (m1, m2) = (m1 as i64 + 1, m2 as i64 + 1);
+        let x = x as i64;
+        let (r, l) = crt(&[(x % m1, m1), (x % m2, m2)]).unwrap();
+        assert_eq!(m1 / gcd(m1, m2) * m2, l);
+        assert_eq!(x % l, r);
+    }
+
+    #[quickcheck]
+    fn test_concat_split_roundtrip(a: u32, b: u16) {
+        let (a, b) = (a as u64, b as u64);
+        let c = concat_digits(a, b).unwrap();
+        assert_eq!((a, b), split_digits(c, num_digits(b)));
+    }
+}
@@ -0,0 +1,406 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+/// Error from one of the parsing helpers. `line` and `column` are 1-based and
+/// relative to the string that was passed in.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseErrorKind {
+    InvalidNumber(String),
+    InvalidField(String),
+    WrongFieldCount { expected: usize, found: usize },
+    UnexpectedChar(char),
+    RaggedRow { expected: usize, found: usize },
+}
+
+impl ParseError {
+    fn new(line: usize, column: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError { line, column, kind }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::InvalidNumber(s) => write!(f, "invalid number '{}'", s),
+            ParseErrorKind::InvalidField(s) => write!(f, "invalid field '{}'", s),
+            ParseErrorKind::WrongFieldCount { expected, found } => {
+                write!(f, "expected {} fields, found {}", expected, found)
+            }
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected character '{}'", c),
+            ParseErrorKind::RaggedRow { expected, found } => {
+                write!(f, "expected row of length {}, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Extracts every integer in `s`, ignoring everything in between. A `-`
+/// directly in front of a digit is treated as a sign, so
+/// `"p=0,4 v=3,-3"` gives `[0, 4, 3, -3]`, unless it directly follows another
+/// digit, so the range `"2-4"` gives `[2, 4]`.
+pub fn ints<T: FromStr>(s: &str) -> Result<Vec<T>, ParseError> {
+    Ok(ints_with_positions(s)?
+        .into_iter()
+        .map(|(value, _, _)| value)
+        .collect())
+}
+
+/// Same as [`ints`], but also returns the 1-based line and column where each
+/// integer starts.
+fn ints_with_positions<T: FromStr>(s: &str) -> Result<Vec<(T, usize, usize)>, ParseError> {
+    let mut result = vec![];
+    for (line_idx, line) in s.lines().enumerate() {
+        let bytes = line.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            let negative = bytes[i] == b'-'
+                && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+                && !(i > 0 && bytes[i - 1].is_ascii_digit());
+            if !negative && !bytes[i].is_ascii_digit() {
+                i += 1;
+                continue;
+            }
+
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            let token = &line[start..i];
+            let value = token.parse::<T>().map_err(|_| {
+                ParseError::new(
+                    line_idx + 1,
+                    start + 1,
+                    ParseErrorKind::InvalidNumber(token.to_string()),
+                )
+            })?;
+            result.push((value, line_idx + 1, start + 1));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Same as [`ints`], but requires exactly `N` integers, so the result can be
+/// destructured directly. Too many integers are reported at the first extra
+/// one, and too few at the end of `s`.
+pub fn ints_n<const N: usize, T: FromStr>(s: &str) -> Result<[T; N], ParseError> {
+    let v = ints_with_positions(s)?;
+    if v.len() != N {
+        let (line, column) = match v.get(N) {
+            Some(&(_, line, column)) => (line, column),
+            None => (
+                s.lines().count().max(1),
+                s.lines().last().map_or(0, str::len) + 1,
+            ),
+        };
+        let found = v.len();
+        return Err(ParseError::new(
+            line,
+            column,
+            ParseErrorKind::WrongFieldCount { expected: N, found },
+        ));
+    }
+
+    let values: Vec<T> = v.into_iter().map(|(value, _, _)| value).collect();
+    Ok(values
+        .try_into()
+        .unwrap_or_else(|_| unreachable!("length checked above")))
+}
+
+/// Exactly `N` integers from each non-empty line of `input`, with errors
+/// reported at their line in `input`.
+pub fn int_rows<const N: usize, T: FromStr>(input: &str) -> Result<Vec<[T; N]>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| ints_n(line).map_err(|e| ParseError { line: i + 1, ..e }))
+        .collect()
+}
+
+/// Splits the input into sections separated by blank lines, dropping any
+/// empty ones.
+pub fn sections(input: &str) -> Vec<&str> {
+    let mut result = vec![];
+    let mut start = None;
+    let mut end = 0;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(s) = start.take() {
+                result.push(input[s..end].trim_end());
+            }
+        } else {
+            start.get_or_insert(offset);
+            end = offset + line.len();
+        }
+        offset += line.len();
+    }
+    if let Some(s) = start {
+        result.push(input[s..end].trim_end());
+    }
+
+    result
+}
+
+/// A tuple that can be parsed from a list of string fields.
+pub trait Record: Sized {
+    const FIELDS: usize;
+
+    /// Parses the fields, returning the index of the first invalid field on
+    /// failure.
+    fn from_fields(fields: &[&str]) -> Result<Self, usize>;
+}
+
+macro_rules! impl_record {
+    ($n:expr; $($t:ident $i:tt),+) => {
+        impl<$($t: FromStr),+> Record for ($($t,)+) {
+            const FIELDS: usize = $n;
+
+            fn from_fields(fields: &[&str]) -> Result<Self, usize> {
+                Ok(($(fields[$i].parse::<$t>().map_err(|_| $i as usize)?,)+))
+            }
+        }
+    };
+}
+
+impl_record!(1; A 0);
+impl_record!(2; A 0, B 1);
+impl_record!(3; A 0, B 1, C 2);
+impl_record!(4; A 0, B 1, C 2, D 3);
+impl_record!(5; A 0, B 1, C 2, D 3, E 4);
+impl_record!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+
+/// Parses one tuple per non-empty line, with fields separated by `delim`. A
+/// whitespace-only `delim` splits on runs of whitespace instead.
+///
+/// # Examples
+///
+/// ```
+/// let v: Vec<(u32, u32)> = records("3   4\n4   3", " ").unwrap();
+/// assert_eq!(vec![(3, 4), (4, 3)], v);
+/// ```
+pub fn records<T: Record>(input: &str, delim: &str) -> Result<Vec<T>, ParseError> {
+    let mut result = vec![];
+    for (line_idx, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields: Vec<(usize, &str)> = if delim.trim().is_empty() {
+            line.split_whitespace()
+                .map(|f| (f.as_ptr() as usize - line.as_ptr() as usize, f))
+                .collect()
+        } else {
+            line.split(delim)
+                .map(|f| (f.as_ptr() as usize - line.as_ptr() as usize, f.trim()))
+                .collect()
+        };
+
+        if fields.len() != T::FIELDS {
+            return Err(ParseError::new(
+                line_idx + 1,
+                1,
+                ParseErrorKind::WrongFieldCount {
+                    expected: T::FIELDS,
+                    found: fields.len(),
+                },
+            ));
+        }
+
+        let strs: Vec<&str> = fields.iter().map(|f| f.1).collect();
+        let record = T::from_fields(&strs).map_err(|i| {
+            ParseError::new(
+                line_idx + 1,
+                fields[i].0 + 1,
+                ParseErrorKind::InvalidField(fields[i].1.to_string()),
+            )
+        })?;
+        result.push(record);
+    }
+
+    Ok(result)
+}
+
+/// Parses a rectangular grid, mapping each character with `f`. Rows are
+/// indexed by y and columns by x, matching [`crate::utils::Point`].
+pub fn grid<T, F>(input: &str, f: F) -> Result<Vec<Vec<T>>, ParseError>
+where
+    F: Fn(char) -> Option<T>,
+{
+    let mut result: Vec<Vec<T>> = vec![];
+    for (line_idx, line) in input.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(col, c)| {
+                f(c).ok_or(ParseError::new(
+                    line_idx + 1,
+                    col + 1,
+                    ParseErrorKind::UnexpectedChar(c),
+                ))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(first) = result.first() {
+            if first.len() != row.len() {
+                return Err(ParseError::new(
+                    line_idx + 1,
+                    1,
+                    ParseErrorKind::RaggedRow {
+                        expected: first.len(),
+                        found: row.len(),
+                    },
+                ));
+            }
+        }
+        result.push(row);
+    }
+
+    Ok(result)
+}
+
+/// Parses a grid of characters as-is.
+pub fn char_grid(input: &str) -> Vec<Vec<char>> {
+    grid(input, Some).expect("any grid of characters is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(vec![0, 4, 3, -3], ints::<i64>("p=0,4 v=3,-3").unwrap());
+        assert_eq!(
+            vec![94, 34, 8400],
+            ints::<u64>("Button A: X+94, Y+34\nPrize: X=8400").unwrap()
+        );
+        assert_eq!(vec![5, 3], ints::<i32>("5-3").unwrap());
+        assert!(ints::<i32>("no numbers - here").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ints_error_position() {
+        assert_eq!(
+            ParseError::new(2, 4, ParseErrorKind::InvalidNumber("-3".to_string())),
+            ints::<u64>("1 2\nab -3").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_ints_n() {
+        let [a, b] = ints_n::<2, u8>("a=1, b=2").unwrap();
+        assert_eq!((1, 2), (a, b));
+        assert_eq!(
+            ParseErrorKind::WrongFieldCount {
+                expected: 2,
+                found: 3
+            },
+            ints_n::<2, u8>("1 2 3").unwrap_err().kind
+        );
+        assert_eq!(
+            ParseError::new(
+                2,
+                3,
+                ParseErrorKind::WrongFieldCount {
+                    expected: 2,
+                    found: 3
+                }
+            ),
+            ints_n::<2, u8>("1\n2 3").unwrap_err()
+        );
+        assert_eq!((1, 4), {
+            let e = ints_n::<2, u8>("x=1").unwrap_err();
+            (e.line, e.column)
+        });
+    }
+
+    #[test]
+    fn test_int_rows() {
+        assert_eq!(
+            vec![[1, 2], [3, -4]],
+            int_rows::<2, i32>("1 2\n\n3,-4\n").unwrap()
+        );
+        assert_eq!(
+            ParseError::new(
+                3,
+                2,
+                ParseErrorKind::WrongFieldCount {
+                    expected: 2,
+                    found: 1
+                }
+            ),
+            int_rows::<2, i32>("1 2\n3 4\n5\n7 8").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        assert_eq!(vec!["a\nb", "c", "d"], sections("\na\nb\n\n\nc\r\n\r\nd\n"));
+        assert!(sections("").is_empty());
+    }
+
+    #[test]
+    fn test_records() {
+        let v: Vec<(u32, u32)> = records("3   4\n4   3\n", " ").unwrap();
+        assert_eq!(vec![(3, 4), (4, 3)], v);
+
+        let v: Vec<(u8, char, String)> = records("1|x|abc", "|").unwrap();
+        assert_eq!(vec![(1, 'x', "abc".to_string())], v);
+    }
+
+    #[test]
+    fn test_records_errors() {
+        assert_eq!(
+            ParseError::new(2, 4, ParseErrorKind::InvalidField("x".to_string())),
+            records::<(u32, u32)>("1|2\n34|x", "|").unwrap_err()
+        );
+        assert_eq!(
+            ParseError::new(
+                1,
+                1,
+                ParseErrorKind::WrongFieldCount {
+                    expected: 2,
+                    found: 3
+                }
+            ),
+            records::<(u32, u32)>("1 2 3", " ").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_grid() {
+        let g = grid("12\n34", |c| c.to_digit(10)).unwrap();
+        assert_eq!(vec![vec![1, 2], vec![3, 4]], g);
+        assert_eq!(
+            ParseError::new(2, 2, ParseErrorKind::UnexpectedChar('x')),
+            grid("12\n3x", |c| c.to_digit(10)).unwrap_err()
+        );
+        assert_eq!(
+            ParseError::new(
+                2,
+                1,
+                ParseErrorKind::RaggedRow {
+                    expected: 2,
+                    found: 3
+                }
+            ),
+            grid("12\n345", |c| c.to_digit(10)).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::ops::Range;
+
+type IntType = i64;
+
+/// Set of integers stored as sorted, disjoint, non-adjacent half-open ranges.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct RangeSet {
+    ranges: Vec<Range<IntType>>,
+}
+
+impl RangeSet {
+    pub fn new() -> RangeSet {
+        RangeSet { ranges: vec![] }
+    }
+
+    /// Adds every integer in `range` to the set.
+    pub fn insert(&mut self, range: Range<IntType>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // Ranges that overlap or touch the new one get merged into it
+        let lo = self.ranges.partition_point(|r| r.end < range.start);
+        let hi = self.ranges.partition_point(|r| r.start <= range.end);
+        let mut merged = range;
+        if lo < hi {
+            merged.start = merged.start.min(self.ranges[lo].start);
+            merged.end = merged.end.max(self.ranges[hi - 1].end);
+        }
+        self.ranges.splice(lo..hi, [merged]);
+    }
+
+    /// Removes every integer in `range` from the set.
+    pub fn remove(&mut self, range: Range<IntType>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let lo = self.ranges.partition_point(|r| r.end <= range.start);
+        let hi = self.ranges.partition_point(|r| r.start < range.end);
+        let mut kept = vec![];
+        if lo < hi {
+            let first = &self.ranges[lo];
+            if first.start < range.start {
+                kept.push(first.start..range.start);
+            }
+            let last = &self.ranges[hi - 1];
+            if last.end > range.end {
+                kept.push(range.end..last.end);
+            }
+        }
+        self.ranges.splice(lo..hi, kept);
+    }
+
+    /// First run of at least `n` integers within `bounds` that is not in the
+    /// set.
+    pub fn first_gap(&self, n: IntType, bounds: Range<IntType>) -> Option<Range<IntType>> {
+        let mut start = bounds.start;
+        for r in self.ranges.iter() {
+            if r.end <= start {
+                continue;
+            }
+            let end = r.start.min(bounds.end);
+            if end - start >= n {
+                return Some(start..end);
+            }
+            start = start.max(r.end);
+            if start >= bounds.end {
+                return None;
+            }
+        }
+
+        (bounds.end - start >= n).then_some(start..bounds.end)
+    }
+}
+
+impl FromIterator<Range<IntType>> for RangeSet {
+    fn from_iter<T: IntoIterator<Item = Range<IntType>>>(iter: T) -> Self {
+        let mut set = RangeSet::new();
+        for r in iter {
+            set.insert(r);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+    use std::collections::BTreeSet;
+
+    /// Small arbitrary ranges.
+    fn ranges(ranges: Vec<(u8, u8)>) -> Vec<Range<IntType>> {
+        ranges
+            .into_iter()
+            .map(|(a, len)| {
+                let a = (a % 64) as IntType;
+                a..a + (len % 8) as IntType
+            })
+            .collect()
+    }
+
+    fn flatten(set: &RangeSet) -> BTreeSet<IntType> {
+        set.ranges.iter().cloned().flatten().collect()
+    }
+
+    fn is_normalized(set: &RangeSet) -> bool {
+        set.ranges.iter().all(|r| !r.is_empty())
+            && set.ranges.windows(2).all(|w| w[0].end < w[1].start)
+    }
+
+    #[test]
+    fn test_insert_merges() {
+        let set: RangeSet = [0..2, 5..7, 2..3, 8..9, 6..8].into_iter().collect();
+        assert_eq!(vec![0..3, 5..9], set.ranges);
+    }
+
+    #[test]
+    fn test_remove_splits() {
+        let mut set = RangeSet::new();
+        set.insert(0..10);
+        set.remove(3..5);
+        assert_eq!(vec![0..3, 5..10], set.ranges);
+    }
+
+    #[test]
+    fn test_first_gap() {
+        let set: RangeSet = [0..2, 4..5, 8..12].into_iter().collect();
+        assert_eq!(Some(2..4), set.first_gap(2, 0..20));
+        assert_eq!(Some(5..8), set.first_gap(3, 0..20));
+        assert_eq!(Some(12..20), set.first_gap(4, 0..20));
+        assert_eq!(None, set.first_gap(4, 0..14));
+    }
+
+    #[quickcheck]
+    fn test_insert_remove_match_brute_force(inserted: Vec<(u8, u8)>, removed: Vec<(u8, u8)>) {
+        let mut set = RangeSet::new();
+        let mut brute = BTreeSet::new();
+        for (add, remove) in ranges(inserted).into_iter().zip(ranges(removed)) {
+            set.insert(add.clone());
+            brute.extend(add);
+            set.remove(remove.clone());
+            for v in remove {
+                brute.remove(&v);
+            }
+            assert!(is_normalized(&set));
+            assert_eq!(brute, flatten(&set));
+        }
+    }
+
+    #[quickcheck]
+    fn test_first_gap_matches_brute_force(a: Vec<(u8, u8)>, n: u8) {
+        let a: RangeSet = ranges(a).into_iter().collect();
+        let a_brute = flatten(&a);
+        let n = (n % 10) as IntType + 1;
+        let brute = (0..80).find(|&s| (s..s + n).all(|v| v < 80 && !a_brute.contains(&v)));
+        assert_eq!(brute, a.first_gap(n, 0..80).map(|r| r.start));
+    }
+}
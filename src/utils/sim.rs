@@ -0,0 +1,311 @@
+use std::io::{self, BufRead, Write};
+
+use crate::utils::viz::Canvas;
+
+/// A deterministic simulation that advances in discrete steps.
+pub trait Simulation {
+    /// Everything needed to put the simulation back into an earlier state.
+    type Snapshot: Clone;
+
+    /// Advances one step. Returns `false`, without changing anything, once
+    /// the simulation has finished.
+    fn step(&mut self) -> bool;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    fn restore(&mut self, snapshot: Self::Snapshot);
+
+    /// Draws the current state.
+    fn canvas(&self) -> Canvas;
+}
+
+/// Drives a simulation forwards and backwards. A snapshot is kept every
+/// `interval` steps, and going back restores the closest earlier snapshot
+/// and replays from there, so long runs don't need a snapshot per step.
+pub struct Runner<S: Simulation> {
+    sim: S,
+    interval: usize,
+    checkpoints: Vec<S::Snapshot>,
+    step: usize,
+    finished: bool,
+}
+
+impl<S: Simulation> Runner<S> {
+    pub fn new(sim: S) -> Runner<S> {
+        Runner::with_interval(sim, 100)
+    }
+
+    pub fn with_interval(sim: S, interval: usize) -> Runner<S> {
+        assert!(interval > 0, "interval must be positive");
+        Runner {
+            checkpoints: vec![sim.snapshot()],
+            sim,
+            interval,
+            step: 0,
+            finished: false,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn sim(&self) -> &S {
+        &self.sim
+    }
+
+    /// Number of steps taken from the initial state.
+    pub fn current_step(&self) -> usize {
+        self.step
+    }
+
+    /// Whether the simulation has been seen to finish at the current step.
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Takes one step. Returns `false` if the simulation has finished.
+    pub fn forward(&mut self) -> bool {
+        if self.finished || !self.sim.step() {
+            self.finished = true;
+            return false;
+        }
+
+        self.step += 1;
+        if self.step.is_multiple_of(self.interval)
+            && self.checkpoints.len() == self.step / self.interval
+        {
+            self.checkpoints.push(self.sim.snapshot());
+        }
+        true
+    }
+
+    /// Undoes one step. Returns `false` at the initial state.
+    pub fn back(&mut self) -> bool {
+        if self.step == 0 {
+            return false;
+        }
+
+        self.jump_to(self.step - 1);
+        true
+    }
+
+    /// Moves to step `n`, or to the last step if the simulation finishes
+    /// before `n`. Returns the step reached.
+    pub fn jump_to(&mut self, n: usize) -> usize {
+        if n < self.step {
+            let k = n / self.interval;
+            self.sim.restore(self.checkpoints[k].clone());
+            self.step = k * self.interval;
+            self.finished = false;
+        }
+        while self.step < n && self.forward() {}
+
+        self.step
+    }
+
+    /// Runs until the simulation finishes and returns the number of steps.
+    pub fn run_to_end(&mut self) -> usize {
+        while self.forward() {}
+        self.step
+    }
+}
+
+/// Type-erased [`Runner`], so solutions can hand one to the command line.
+pub trait Debugger {
+    fn current_step(&self) -> usize;
+    fn finished(&self) -> bool;
+    fn forward(&mut self) -> bool;
+    fn back(&mut self) -> bool;
+    fn jump_to(&mut self, n: usize) -> usize;
+    fn run_to_end(&mut self) -> usize;
+    fn render(&self, color: bool) -> String;
+}
+
+impl<S: Simulation> Debugger for Runner<S> {
+    fn current_step(&self) -> usize {
+        Runner::current_step(self)
+    }
+
+    fn finished(&self) -> bool {
+        Runner::finished(self)
+    }
+
+    fn forward(&mut self) -> bool {
+        Runner::forward(self)
+    }
+
+    fn back(&mut self) -> bool {
+        Runner::back(self)
+    }
+
+    fn jump_to(&mut self, n: usize) -> usize {
+        Runner::jump_to(self, n)
+    }
+
+    fn run_to_end(&mut self) -> usize {
+        Runner::run_to_end(self)
+    }
+
+    fn render(&self, color: bool) -> String {
+        self.sim.canvas().render(color)
+    }
+}
+
+const HELP: &str = "commands: [n]ext [k], [b]ack [k], [g]oto <step>, [e]nd, [q]uit";
+
+/// Reads commands from `input` and prints the grid after each one, until
+/// `q` or the end of the input. An empty line repeats `n`.
+pub fn interact(
+    dbg: &mut dyn Debugger,
+    input: impl BufRead,
+    mut output: impl Write,
+    color: bool,
+) -> io::Result<()> {
+    writeln!(output, "{}", HELP)?;
+    show(dbg, &mut output, color)?;
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("n");
+        let arg = words.next().map(|w| w.parse::<usize>());
+        match (command, arg) {
+            ("n", None) => {
+                dbg.forward();
+            }
+            ("n", Some(Ok(k))) => {
+                for _ in 0..k {
+                    dbg.forward();
+                }
+            }
+            ("b", None) => {
+                dbg.back();
+            }
+            ("b", Some(Ok(k))) => {
+                dbg.jump_to(dbg.current_step().saturating_sub(k));
+            }
+            ("g", Some(Ok(n))) => {
+                dbg.jump_to(n);
+            }
+            ("e", None) => {
+                dbg.run_to_end();
+            }
+            ("q", None) => return Ok(()),
+            _ => {
+                writeln!(output, "{}", HELP)?;
+                continue;
+            }
+        }
+        show(dbg, &mut output, color)?;
+    }
+
+    Ok(())
+}
+
+fn show(dbg: &dyn Debugger, output: &mut impl Write, color: bool) -> io::Result<()> {
+    write!(output, "{}", dbg.render(color))?;
+    let status = if dbg.finished() { " (finished)" } else { "" };
+    writeln!(output, "step {}{}", dbg.current_step(), status)?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Point;
+    use std::io::Cursor;
+
+    /// A dot bouncing between the ends of a line for a fixed number of steps.
+    struct Bounce {
+        pos: usize,
+        dir: isize,
+        steps_left: usize,
+        width: usize,
+    }
+
+    impl Simulation for Bounce {
+        type Snapshot = (usize, isize, usize);
+
+        fn step(&mut self) -> bool {
+            if self.steps_left == 0 {
+                return false;
+            }
+            if (self.pos == 0 && self.dir < 0) || (self.pos + 1 == self.width && self.dir > 0) {
+                self.dir = -self.dir;
+            }
+            self.pos = (self.pos as isize + self.dir) as usize;
+            self.steps_left -= 1;
+            true
+        }
+
+        fn snapshot(&self) -> Self::Snapshot {
+            (self.pos, self.dir, self.steps_left)
+        }
+
+        fn restore(&mut self, snapshot: Self::Snapshot) {
+            (self.pos, self.dir, self.steps_left) = snapshot;
+        }
+
+        fn canvas(&self) -> Canvas {
+            let mut canvas = Canvas::new(self.width, 1);
+            canvas.set(Point::new(self.pos, 0), 'o', None);
+            canvas
+        }
+    }
+
+    fn bounce(steps: usize) -> Bounce {
+        Bounce {
+            pos: 0,
+            dir: 1,
+            steps_left: steps,
+            width: 4,
+        }
+    }
+
+    #[test]
+    fn test_back_matches_forward() {
+        let mut runner = Runner::with_interval(bounce(20), 3);
+        let mut states = vec![runner.sim().snapshot()];
+        while runner.forward() {
+            states.push(runner.sim().snapshot());
+        }
+        assert_eq!(20, runner.current_step());
+        assert!(runner.finished());
+
+        for n in (0..20).rev() {
+            assert!(runner.back());
+            assert_eq!(n, runner.current_step());
+            assert_eq!(states[n], runner.sim().snapshot());
+        }
+        assert!(!runner.back());
+
+        assert_eq!(7, runner.jump_to(7));
+        assert_eq!(states[7], runner.sim().snapshot());
+        assert_eq!(20, runner.jump_to(50));
+    }
+
+    #[test]
+    fn test_interact() {
+        let mut runner = Runner::new(bounce(5));
+        let mut output = vec![];
+        interact(
+            &mut runner,
+            Cursor::new("\nn 2\nb\ng 4\ne\nx\nq\nn\n"),
+            &mut output,
+            false,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let steps: Vec<&str> = output.lines().filter(|l| l.starts_with("step")).collect();
+        assert_eq!(
+            vec![
+                "step 0",
+                "step 1",
+                "step 3",
+                "step 2",
+                "step 4",
+                "step 5 (finished)"
+            ],
+            steps
+        );
+        assert!(output.contains("o   \nstep 0"));
+        assert!(output.contains(" o  \nstep 1"));
+    }
+}
@@ -1,4 +1,41 @@
+use std::io;
+
+use crate::utils::{sim::Debugger, viz::Visualizer};
+
+const UNSOLVED: &str = "Unsolved: ";
+
+/// Answer for a puzzle the solution couldn't solve on this input, e.g. because
+/// the maze has no path. `reason` is shown to the user.
+pub fn unsolved(reason: &str) -> String {
+    format!("{}{}", UNSOLVED, reason)
+}
+
+/// Whether `answer` actually answers the puzzle, rather than being empty or
+/// made with [`unsolved`].
+pub fn is_solved(answer: &str) -> bool {
+    !answer.trim().is_empty() && !answer.starts_with(UNSOLVED)
+}
+
 pub trait Solution {
     fn part_1(&self, input: String) -> String;
     fn part_2(&self, input: String) -> String;
+
+    /// Whether the day has a second puzzle. The last day of a year doesn't:
+    /// its second star is awarded for finishing every other puzzle, and its
+    /// `part_2` only says so.
+    fn has_part_2(&self) -> bool {
+        true
+    }
+
+    /// Shows intermediate states of the puzzle through `viz`. Returns
+    /// `Ok(false)` for solutions that don't support it.
+    fn visualize(&self, _input: String, _viz: &mut Visualizer) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    /// Steps through the puzzle as a simulation. Returns `None` for solutions
+    /// that don't support it.
+    fn step_through(&self, _input: String) -> Option<Box<dyn Debugger>> {
+        None
+    }
 }
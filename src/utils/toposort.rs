@@ -0,0 +1,231 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    error::Error,
+    fmt,
+    hash::Hash,
+};
+
+/// Nodes that form a cycle: each node must come before the next one, and the
+/// last before the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<N> {
+    pub nodes: Vec<N>,
+}
+
+impl<N: fmt::Debug> fmt::Display for Cycle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ordering has a cycle:")?;
+        for node in self.nodes.iter() {
+            write!(f, " {:?} ->", node)?;
+        }
+        match self.nodes.first() {
+            Some(first) => write!(f, " {:?}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<N: fmt::Debug> Error for Cycle<N> {}
+
+/// Orders `nodes` so that every node comes before its `successors`, using
+/// Kahn's algorithm. Whenever there is a choice the node that appears first in
+/// `nodes` is taken, so nodes that are already in a valid order stay put.
+/// Successors that are not in `nodes` are ignored.
+pub fn toposort<N, F, I>(nodes: &[N], successors: F) -> Result<Vec<N>, Cycle<N>>
+where
+    N: Hash + Eq + Clone,
+    F: Fn(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let index: HashMap<&N, usize> = nodes.iter().enumerate().map(|(i, n)| (n, i)).collect();
+    let mut edges = vec![vec![]; nodes.len()];
+    let mut predecessors = vec![vec![]; nodes.len()];
+    let mut in_degree = vec![0; nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        for successor in successors(node) {
+            if let Some(&j) = index.get(&successor) {
+                edges[i].push(j);
+                predecessors[j].push(i);
+                in_degree[j] += 1;
+            }
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..nodes.len())
+        .filter(|&i| in_degree[i] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(nodes.len());
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(nodes[i].clone());
+        for &j in edges[i].iter() {
+            in_degree[j] -= 1;
+            if in_degree[j] == 0 {
+                ready.push(Reverse(j));
+            }
+        }
+    }
+
+    if order.len() < nodes.len() {
+        let cycle = find_cycle(&predecessors, &in_degree);
+        return Err(Cycle {
+            nodes: cycle.into_iter().map(|i| nodes[i].clone()).collect(),
+        });
+    }
+
+    Ok(order)
+}
+
+/// Finds a cycle among the nodes Kahn's algorithm could not place. Every such
+/// node has an unplaced predecessor, so walking backwards must repeat a node.
+fn find_cycle(predecessors: &[Vec<usize>], in_degree: &[usize]) -> Vec<usize> {
+    let stuck = |i: &usize| in_degree[*i] > 0;
+    let mut node = (0..in_degree.len()).find(stuck).unwrap();
+    let mut path = vec![];
+    let mut position = HashMap::new();
+    while !position.contains_key(&node) {
+        position.insert(node, path.len());
+        path.push(node);
+        node = *predecessors[node].iter().find(|p| stuck(p)).unwrap();
+    }
+
+    let mut cycle = path.split_off(position[&node]);
+    cycle.reverse();
+    cycle
+}
+
+/// A set of "a comes before b" rules.
+#[derive(Debug, Clone)]
+pub struct PartialOrder<T> {
+    after: HashMap<T, HashSet<T>>,
+}
+
+impl<T: Hash + Eq + Clone> PartialOrder<T> {
+    pub fn new() -> PartialOrder<T> {
+        PartialOrder {
+            after: HashMap::new(),
+        }
+    }
+
+    /// Adds the rule that `before` comes before `after`.
+    pub fn add(&mut self, before: T, after: T) {
+        self.after.entry(before).or_default().insert(after);
+    }
+
+    /// Whether there is a rule putting `a` before `b`. Rules are not chained.
+    #[cfg(test)]
+    pub fn precedes(&self, a: &T, b: &T) -> bool {
+        self.after.get(a).is_some_and(|after| after.contains(b))
+    }
+
+    /// Whether no rule puts a later element of `seq` before an earlier one.
+    pub fn is_consistent(&self, seq: &[T]) -> bool {
+        self.first_violation(seq).is_none()
+    }
+
+    /// Indices `(i, j)` with `i < j` of the first pair of elements in `seq`
+    /// where a rule says `seq[j]` comes before `seq[i]`.
+    pub fn first_violation(&self, seq: &[T]) -> Option<(usize, usize)> {
+        let position: HashMap<&T, usize> = seq.iter().enumerate().map(|(i, x)| (x, i)).collect();
+        seq.iter()
+            .enumerate()
+            .filter_map(|(j, x)| {
+                let earliest = self
+                    .after
+                    .get(x)?
+                    .iter()
+                    .filter_map(|y| position.get(y).copied())
+                    .filter(|&i| i < j)
+                    .min()?;
+                Some((earliest, j))
+            })
+            .min()
+    }
+
+    /// Reorders the distinct elements of `seq` to satisfy every rule between
+    /// them, keeping their original order wherever the rules allow. Only the
+    /// rules among the elements of `seq` matter, so the full rule set may
+    /// contain cycles elsewhere.
+    pub fn sort(&self, seq: &[T]) -> Result<Vec<T>, Cycle<T>> {
+        toposort(seq, |x| self.after.get(x).into_iter().flatten().cloned())
+    }
+}
+
+impl<T: Hash + Eq + Clone> Default for PartialOrder<T> {
+    fn default() -> Self {
+        PartialOrder::new()
+    }
+}
+
+impl<T: Hash + Eq + Clone> FromIterator<(T, T)> for PartialOrder<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut order = PartialOrder::new();
+        for (before, after) in iter {
+            order.add(before, after);
+        }
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn test_toposort_is_stable() {
+        let order: PartialOrder<char> = [('d', 'b')].into_iter().collect();
+        assert_eq!(
+            vec!['a', 'c', 'd', 'b', 'e'],
+            order.sort(&['a', 'b', 'c', 'd', 'e']).unwrap()
+        );
+        assert_eq!(vec!['a', 'c'], order.sort(&['a', 'c']).unwrap());
+    }
+
+    #[test]
+    fn test_cycle_is_reported() {
+        let order: PartialOrder<u8> = [(1, 2), (2, 3), (3, 1), (3, 4), (0, 1)]
+            .into_iter()
+            .collect();
+        let cycle = order.sort(&[0, 1, 2, 3, 4]).unwrap_err();
+        assert_eq!(3, cycle.nodes.len());
+        for (i, a) in cycle.nodes.iter().enumerate() {
+            let b = &cycle.nodes[(i + 1) % cycle.nodes.len()];
+            assert!(order.precedes(a, b));
+        }
+
+        // The cycle doesn't matter without all of its nodes
+        assert_eq!(vec![0, 2, 3, 4], order.sort(&[0, 3, 2, 4]).unwrap());
+    }
+
+    #[test]
+    fn test_first_violation() {
+        let order: PartialOrder<u8> = [(1, 2), (3, 4)].into_iter().collect();
+        assert!(order.is_consistent(&[1, 3, 2, 4]));
+        assert_eq!(Some((1, 3)), order.first_violation(&[5, 2, 4, 1, 3]));
+    }
+
+    #[quickcheck]
+    fn test_sort_of_acyclic_order_is_consistent(rules: Vec<(u8, u8)>, seq: Vec<u8>) {
+        // Rules that only go from smaller to larger numbers can't form a cycle
+        let order: PartialOrder<u8> = rules
+            .into_iter()
+            .map(|(a, b)| (a % 16, b % 16))
+            .filter(|(a, b)| a < b)
+            .collect();
+        let mut seen = HashSet::new();
+        let seq: Vec<u8> = seq
+            .into_iter()
+            .map(|x| x % 16)
+            .filter(|&x| seen.insert(x))
+            .collect();
+
+        let sorted = order.sort(&seq).unwrap();
+        assert!(order.is_consistent(&sorted));
+        assert_eq!(seen, sorted.iter().copied().collect());
+        if order.is_consistent(&seq) {
+            assert_eq!(seq, sorted);
+        }
+    }
+}
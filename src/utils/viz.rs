@@ -0,0 +1,325 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use crate::utils::Point;
+
+pub type Rgb = [u8; 3];
+
+pub const WHITE: Rgb = [255, 255, 255];
+pub const BLACK: Rgb = [0, 0, 0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub color: Option<Rgb>,
+}
+
+impl Cell {
+    /// Whether the cell shows anything. Spaces and dots count as background.
+    fn is_filled(&self) -> bool {
+        self.ch != ' ' && self.ch != '.'
+    }
+
+    /// Pixel colour when drawn to an image.
+    fn pixel(&self) -> Rgb {
+        match self.color {
+            Some(color) => color,
+            None if self.is_filled() => WHITE,
+            None => BLACK,
+        }
+    }
+}
+
+/// Grid of characters with optional colours, rendered to the terminal as text
+/// or written out as an image with one square of pixels per cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            cells: vec![
+                Cell {
+                    ch: ' ',
+                    color: None
+                };
+                width * height
+            ],
+        }
+    }
+
+    /// Canvas showing a character grid as is.
+    pub fn from_chars(grid: &[Vec<char>]) -> Canvas {
+        let width = grid.iter().map(|r| r.len()).max().unwrap_or(0);
+        let mut canvas = Canvas::new(width, grid.len());
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &ch) in row.iter().enumerate() {
+                canvas.set(Point::new(x, y), ch, None);
+            }
+        }
+        canvas
+    }
+
+    /// Canvas with a `#` at each point. Points outside the canvas are
+    /// skipped.
+    pub fn from_points<'a>(
+        points: impl IntoIterator<Item = &'a Point>,
+        width: usize,
+        height: usize,
+        color: Option<Rgb>,
+    ) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for &p in points {
+            if p.x < width && p.y < height {
+                canvas.set(p, '#', color);
+            }
+        }
+        canvas
+    }
+
+    pub fn get(&self, p: Point) -> Cell {
+        self.cells[p.y * self.width + p.x]
+    }
+
+    pub fn set(&mut self, p: Point, ch: char, color: Option<Rgb>) {
+        self.cells[p.y * self.width + p.x] = Cell { ch, color };
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    /// One line of text per row. With `color` set, coloured cells are wrapped
+    /// in 24-bit ANSI escape codes.
+    pub fn render(&self, color: bool) -> String {
+        let mut out = String::new();
+        for row in self.rows() {
+            for cell in row {
+                match cell.color.filter(|_| color) {
+                    Some([r, g, b]) => {
+                        out += &format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, cell.ch)
+                    }
+                    None => out.push(cell.ch),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Two rows per line using half block characters, which keeps cells
+    /// roughly square in a terminal. Only whether a cell is filled is shown,
+    /// not its character.
+    pub fn render_compact(&self, color: bool) -> String {
+        let empty = Cell {
+            ch: ' ',
+            color: None,
+        };
+        let mut out = String::new();
+        for y in (0..self.height).step_by(2) {
+            for x in 0..self.width {
+                let top = self.get(Point::new(x, y));
+                let bottom = if y + 1 < self.height {
+                    self.get(Point::new(x, y + 1))
+                } else {
+                    empty
+                };
+                let (ch, fg, bg) = match (top.is_filled(), bottom.is_filled()) {
+                    (false, false) => (' ', None, None),
+                    (true, false) => ('▀', top.color, None),
+                    (false, true) => ('▄', bottom.color, None),
+                    (true, true) if !color || top.color == bottom.color => ('█', top.color, None),
+                    (true, true) => ('▀', Some(top.pixel()), Some(bottom.pixel())),
+                };
+                if !color || fg.is_none() {
+                    out.push(ch);
+                    continue;
+                }
+                let [r, g, b] = fg.unwrap();
+                out += &format!("\x1b[38;2;{};{};{}m", r, g, b);
+                if let Some([r, g, b]) = bg {
+                    out += &format!("\x1b[48;2;{};{};{}m", r, g, b);
+                }
+                out.push(ch);
+                out += "\x1b[0m";
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Raw RGB pixel data with each cell drawn as a `scale` by `scale` square.
+    fn pixels(&self, scale: usize) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.cells.len() * scale * scale * 3);
+        for row in self.rows() {
+            for _ in 0..scale {
+                for cell in row {
+                    for _ in 0..scale {
+                        data.extend_from_slice(&cell.pixel());
+                    }
+                }
+            }
+        }
+        data
+    }
+
+    /// Writes the canvas as a PNG image.
+    pub fn write_png(&self, path: &Path, scale: usize) -> io::Result<()> {
+        let w = BufWriter::new(File::create(path)?);
+        let mut encoder =
+            png::Encoder::new(w, (self.width * scale) as u32, (self.height * scale) as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.pixels(scale))
+            .map_err(io::Error::other)
+    }
+}
+
+/// Shows frames in the terminal as an animation, and optionally saves each
+/// one as a numbered PNG in `frames_dir`. Other files a visualization
+/// produces are saved there too.
+#[derive(Debug, Clone)]
+pub struct Visualizer {
+    color: bool,
+    delay: Duration,
+    frames_dir: Option<PathBuf>,
+    frame: usize,
+}
+
+impl Visualizer {
+    /// Pixels per cell in saved frames.
+    const SCALE: usize = 4;
+
+    pub fn new(color: bool, delay: Duration, frames_dir: Option<PathBuf>) -> Visualizer {
+        Visualizer {
+            color,
+            delay,
+            frames_dir,
+            frame: 0,
+        }
+    }
+
+    /// Number of frames shown so far.
+    #[cfg(test)]
+    pub fn frames(&self) -> usize {
+        self.frame
+    }
+
+    /// Saves a visualization that isn't a frame, such as a graph description,
+    /// as `name` in `frames_dir`, and returns where it went.
+    pub fn save(&self, name: &str, contents: &str) -> io::Result<PathBuf> {
+        let dir = self.frames_dir.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("saving {} needs a frames directory", name),
+            )
+        })?;
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(name);
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    /// Shows one frame, one line per row.
+    pub fn frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        self.show(canvas, canvas.render(self.color))
+    }
+
+    /// Shows one frame, two rows per line.
+    pub fn compact_frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        self.show(canvas, canvas.render_compact(self.color))
+    }
+
+    fn show(&mut self, canvas: &Canvas, text: String) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        // Clear the screen and move the cursor to the top left
+        write!(out, "\x1b[2J\x1b[H{}", text)?;
+        writeln!(out, "frame {}", self.frame)?;
+        out.flush()?;
+
+        if let Some(dir) = &self.frames_dir {
+            std::fs::create_dir_all(dir)?;
+            canvas.write_png(
+                &dir.join(format!("frame_{:05}.png", self.frame)),
+                Self::SCALE,
+            )?;
+        }
+
+        self.frame += 1;
+        thread::sleep(self.delay);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canvas() -> Canvas {
+        let grid: Vec<Vec<char>> = ["#.@", "..#", "#.."]
+            .iter()
+            .map(|r| r.chars().collect())
+            .collect();
+        let mut canvas = Canvas::from_chars(&grid);
+        canvas.set(Point::new(2, 0), '@', Some([255, 0, 0]));
+        canvas
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!("#.@\n..#\n#..\n", canvas().render(false));
+        assert_eq!(
+            "#.\x1b[38;2;255;0;0m@\x1b[0m\n..#\n#..\n",
+            canvas().render(true)
+        );
+    }
+
+    #[test]
+    fn test_render_compact() {
+        assert_eq!("▀ █\n▀  \n", canvas().render_compact(false));
+    }
+
+    #[test]
+    fn test_from_points() {
+        let points = [Point::new(0, 0), Point::new(1, 1), Point::new(5, 5)];
+        let canvas = Canvas::from_points(&points, 2, 2, None);
+        assert_eq!("# \n #\n", canvas.render(false));
+    }
+
+    #[test]
+    fn test_pixels() {
+        let pixels = canvas().pixels(2);
+        assert_eq!(6 * 6 * 3, pixels.len());
+        assert_eq!(WHITE, pixels[0..3]);
+        assert_eq!(BLACK, pixels[6..9]);
+        assert_eq!([255, 0, 0], pixels[12..15]);
+        // Second pixel row repeats the first
+        assert_eq!(pixels[0..18], pixels[18..36]);
+    }
+
+    #[test]
+    fn test_write_png() {
+        let dir = std::env::temp_dir().join(format!("aoc-viz-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        canvas().write_png(&dir.join("c.png"), 1).unwrap();
+        let decoder = png::Decoder::new(File::open(dir.join("c.png")).unwrap());
+        let reader = decoder.read_info().unwrap();
+        assert_eq!((3, 3), reader.info().size());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,358 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use crate::utils::sim::Simulation;
+use crate::utils::viz::Canvas;
+use crate::utils::Point;
+
+pub type Word = u64;
+
+/// Register indices.
+pub const A: usize = 0;
+pub const B: usize = 1;
+pub const C: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+impl Instruction {
+    pub fn from_opcode(opcode: u8) -> Instruction {
+        match opcode {
+            0 => Instruction::Adv,
+            1 => Instruction::Bxl,
+            2 => Instruction::Bst,
+            3 => Instruction::Jnz,
+            4 => Instruction::Bxc,
+            5 => Instruction::Out,
+            6 => Instruction::Bdv,
+            7 => Instruction::Cdv,
+            _ => panic!("invalid opcode {}", opcode),
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Adv => "adv",
+            Instruction::Bxl => "bxl",
+            Instruction::Bst => "bst",
+            Instruction::Jnz => "jnz",
+            Instruction::Bxc => "bxc",
+            Instruction::Out => "out",
+            Instruction::Bdv => "bdv",
+            Instruction::Cdv => "cdv",
+        }
+    }
+
+    /// Whether the operand is a combo operand rather than a literal.
+    pub fn takes_combo(&self) -> bool {
+        matches!(
+            self,
+            Instruction::Adv
+                | Instruction::Bst
+                | Instruction::Out
+                | Instruction::Bdv
+                | Instruction::Cdv
+        )
+    }
+
+    /// Operand as it reads in assembly. `bxc` ignores its operand.
+    pub fn format_operand(&self, operand: u8) -> String {
+        match (self, operand) {
+            (Instruction::Bxc, _) => String::new(),
+            (i, 4) if i.takes_combo() => "A".to_string(),
+            (i, 5) if i.takes_combo() => "B".to_string(),
+            (i, 6) if i.takes_combo() => "C".to_string(),
+            (_, o) => o.to_string(),
+        }
+    }
+}
+
+/// One line per instruction, e.g. `0: adv 1` or `4: out B`.
+pub fn disassemble(program: &[u8]) -> Vec<String> {
+    program
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let instruction = Instruction::from_opcode(pair[0]);
+            let operand = pair
+                .get(1)
+                .map_or(String::new(), |&o| instruction.format_operand(o));
+            format!("{}: {} {}", i * 2, instruction.mnemonic(), operand)
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// What a single instruction did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub ip: usize,
+    pub instruction: Instruction,
+    pub operand: u8,
+    pub registers: [Word; 3],
+    pub output: Option<u8>,
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>3}: {} {:<2} A={} B={} C={}",
+            self.ip,
+            self.instruction.mnemonic(),
+            self.instruction.format_operand(self.operand),
+            self.registers[A],
+            self.registers[B],
+            self.registers[C]
+        )?;
+        if let Some(out) = self.output {
+            write!(f, " out={}", out)?;
+        }
+        Ok(())
+    }
+}
+
+/// The 3-bit computer: three registers, a program of 3-bit numbers and an
+/// instruction pointer that moves two numbers at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vm {
+    pub registers: [Word; 3],
+    pub program: Vec<u8>,
+    ip: usize,
+    pub output: Vec<u8>,
+}
+
+impl Vm {
+    pub fn new(registers: [Word; 3], program: Vec<u8>) -> Vm {
+        Vm {
+            registers,
+            program,
+            ip: 0,
+            output: vec![],
+        }
+    }
+
+    pub fn halted(&self) -> bool {
+        self.ip + 1 >= self.program.len()
+    }
+
+    fn combo(&self, operand: u8) -> Word {
+        match operand {
+            0..=3 => operand as Word,
+            4 => self.registers[A],
+            5 => self.registers[B],
+            6 => self.registers[C],
+            _ => panic!("reserved combo operand {}", operand),
+        }
+    }
+
+    fn divide(&self, operand: u8) -> Word {
+        self.registers[A]
+            .checked_shr(self.combo(operand).try_into().unwrap_or(u32::MAX))
+            .unwrap_or(0)
+    }
+
+    /// Runs one instruction and reports what it did, or returns `None` once
+    /// the program has halted.
+    pub fn step(&mut self) -> Option<Trace> {
+        if self.halted() {
+            return None;
+        }
+
+        let ip = self.ip;
+        let instruction = Instruction::from_opcode(self.program[ip]);
+        let operand = self.program[ip + 1];
+        let mut output = None;
+        self.ip += 2;
+        match instruction {
+            Instruction::Adv => self.registers[A] = self.divide(operand),
+            Instruction::Bxl => self.registers[B] ^= operand as Word,
+            Instruction::Bst => self.registers[B] = self.combo(operand) % 8,
+            Instruction::Jnz => {
+                if self.registers[A] != 0 {
+                    self.ip = operand as usize;
+                }
+            }
+            Instruction::Bxc => self.registers[B] ^= self.registers[C],
+            Instruction::Out => {
+                let value = (self.combo(operand) % 8) as u8;
+                self.output.push(value);
+                output = Some(value);
+            }
+            Instruction::Bdv => self.registers[B] = self.divide(operand),
+            Instruction::Cdv => self.registers[C] = self.divide(operand),
+        }
+
+        Some(Trace {
+            ip,
+            instruction,
+            operand,
+            registers: self.registers,
+            output,
+        })
+    }
+
+    /// Runs until the program halts and returns everything it printed.
+    pub fn run(&mut self) -> &[u8] {
+        while self.step().is_some() {}
+        &self.output
+    }
+}
+
+/// Steps through a program one instruction at a time, showing the listing
+/// alongside the last instruction run.
+pub struct Tracer {
+    vm: Vm,
+    listing: Vec<String>,
+    last: Option<Trace>,
+}
+
+impl Tracer {
+    pub fn new(vm: Vm) -> Tracer {
+        Tracer {
+            listing: disassemble(&vm.program),
+            vm,
+            last: None,
+        }
+    }
+}
+
+impl Simulation for Tracer {
+    type Snapshot = (Vm, Option<Trace>);
+
+    fn step(&mut self) -> bool {
+        match self.vm.step() {
+            Some(trace) => {
+                self.last = Some(trace);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn snapshot(&self) -> Self::Snapshot {
+        (self.vm.clone(), self.last.clone())
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        (self.vm, self.last) = snapshot;
+    }
+
+    fn canvas(&self) -> Canvas {
+        let mut lines: Vec<String> = self
+            .listing
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let marker = if i * 2 == self.vm.ip { '>' } else { ' ' };
+                format!("{} {}", marker, line)
+            })
+            .collect();
+        lines.push(String::new());
+        lines.push(match &self.last {
+            Some(trace) => trace.to_string(),
+            None => "(start)".to_string(),
+        });
+        lines.push(format!("output: {}", self.vm.output.iter().join(",")));
+
+        let grid: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+        let mut canvas = Canvas::from_chars(&grid);
+        let row = self.vm.ip / 2;
+        if row < self.listing.len() {
+            canvas.set(Point::new(0, row), '>', Some([255, 0, 0]));
+        }
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(registers: [Word; 3], program: &[u8]) -> Vm {
+        let mut vm = Vm::new(registers, program.to_vec());
+        vm.run();
+        vm
+    }
+
+    #[test]
+    fn test_adv() {
+        assert_eq!(10 >> 2, run([10, 0, 0], &[0, 2]).registers[A]);
+        assert_eq!(40 >> 3, run([40, 3, 0], &[0, 5]).registers[A]);
+    }
+
+    #[test]
+    fn test_bxl() {
+        assert_eq!(26, run([0, 29, 0], &[1, 7]).registers[B]);
+    }
+
+    #[test]
+    fn test_bst() {
+        assert_eq!(1, run([0, 0, 9], &[2, 6]).registers[B]);
+    }
+
+    #[test]
+    fn test_jnz() {
+        // Counts A down by halving until it reaches zero
+        let vm = run([8, 0, 0], &[0, 1, 5, 4, 3, 0]);
+        assert_eq!(vec![4, 2, 1, 0], vm.output);
+        // Falls through when A is zero
+        assert_eq!(vec![0], run([0, 0, 0], &[3, 4, 5, 4]).output);
+    }
+
+    #[test]
+    fn test_bxc() {
+        assert_eq!(44354, run([0, 2024, 43690], &[4, 0]).registers[B]);
+    }
+
+    #[test]
+    fn test_out() {
+        assert_eq!(vec![0, 1, 2], run([10, 0, 0], &[5, 0, 5, 1, 5, 4]).output);
+        let vm = run([2024, 0, 0], &[0, 1, 5, 4, 3, 0]);
+        assert_eq!(vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0], vm.output);
+        assert_eq!(0, vm.registers[A]);
+    }
+
+    #[test]
+    fn test_bdv_cdv() {
+        let vm = run([100, 0, 0], &[6, 3, 7, 2]);
+        assert_eq!([100, 12, 25], vm.registers);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        assert_eq!(
+            vec![
+                "0: bst A",
+                "2: bxl 3",
+                "4: cdv B",
+                "6: bxc",
+                "8: out C",
+                "10: jnz 0"
+            ],
+            disassemble(&[2, 4, 1, 3, 7, 5, 4, 1, 5, 6, 3, 0])
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut vm = Vm::new([10, 0, 0], vec![0, 1, 5, 4]);
+        let first = vm.step().unwrap();
+        assert_eq!("  0: adv 1  A=5 B=0 C=0", first.to_string());
+        let second = vm.step().unwrap();
+        assert_eq!(Some(5), second.output);
+        assert_eq!("  2: out A  A=5 B=0 C=0 out=5", second.to_string());
+        assert_eq!(None, vm.step());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::utils::template::Solution;
+use crate::utils::{parse, template::Solution};
 
 pub struct Sln {}
 
@@ -8,10 +8,7 @@ impl Sln {
     }
 
     fn read_into_vecs(input: String, v1: &mut Vec<u32>, v2: &mut Vec<u32>) {
-        for line in input.lines() {
-            let mut split = line.split_whitespace();
-            let val1 = split.next().unwrap().parse::<u32>().unwrap();
-            let val2 = split.next().unwrap().parse::<u32>().unwrap();
+        for (val1, val2) in parse::records::<(u32, u32)>(&input, " ").unwrap() {
             v1.push(val1);
             v2.push(val2);
         }
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use crate::utils::{template::Solution, Point};
+use crate::utils::{memo::Memo, template::Solution, Point};
 
 pub struct Sln {}
 
@@ -9,7 +9,14 @@ impl Sln {
         Sln {}
     }
 
-    fn hike(map: &Map, start: Point) -> (HashSet<Point>, usize) {
+    /// Summits reachable from `start` and the number of distinct trails to
+    /// them. Trails from a point don't depend on how it was reached, so
+    /// results are shared between trailheads through `memo`.
+    fn hike(
+        map: &Map,
+        memo: &mut Memo<Point, (HashSet<Point>, usize)>,
+        start: Point,
+    ) -> (HashSet<Point>, usize) {
         let current_elevation = match map.elevation(start) {
             Some(e) => e,
             None => return (HashSet::new(), 0),
@@ -19,19 +26,21 @@ impl Sln {
             return (HashSet::from([start]), 1);
         }
 
-        let mut res = HashSet::new();
-        let mut paths = 0;
-        for p in vec![start.up(), start.down(), start.left(), start.right()] {
-            if let Some(e) = map.elevation(p) {
-                if e == current_elevation + 1 {
-                    let sub = Self::hike(&map, p);
-                    res.extend(sub.0);
-                    paths += sub.1;
+        memo.get_or_insert_with(start, |memo| {
+            let mut res = HashSet::new();
+            let mut paths = 0;
+            for p in [start.up(), start.down(), start.left(), start.right()] {
+                if let Some(e) = map.elevation(p) {
+                    if e == current_elevation + 1 {
+                        let sub = Self::hike(map, memo, p);
+                        res.extend(sub.0);
+                        paths += sub.1;
+                    }
                 }
             }
-        }
 
-        (res, paths)
+            (res, paths)
+        })
     }
 }
 
@@ -69,11 +78,12 @@ impl Solution for Sln {
         }
 
         let map = Map::new(map_vec);
+        let mut memo = Memo::new();
         let mut answer = 0;
         for (y, row) in map.m.iter().enumerate() {
             for (x, &col) in row.iter().enumerate() {
                 if col == 0 {
-                    answer += Self::hike(&map, Point::new(x, y)).0.len();
+                    answer += Self::hike(&map, &mut memo, Point::new(x, y)).0.len();
                 }
             }
         }
@@ -92,11 +102,12 @@ impl Solution for Sln {
         }
 
         let map = Map::new(map_vec);
+        let mut memo = Memo::new();
         let mut answer = 0;
         for (y, row) in map.m.iter().enumerate() {
             for (x, &col) in row.iter().enumerate() {
                 if col == 0 {
-                    answer += Self::hike(&map, Point::new(x, y)).1;
+                    answer += Self::hike(&map, &mut memo, Point::new(x, y)).1;
                 }
             }
         }
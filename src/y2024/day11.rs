@@ -1,5 +1,4 @@
-use crate::utils::template::Solution;
-use std::collections::HashMap;
+use crate::utils::{memo::Memo, num, template::Solution, Counter};
 
 pub struct Sln {}
 
@@ -9,57 +8,67 @@ impl Sln {
     }
 
     fn solve(&self, input: String, iterations: usize) -> String {
-        let mut star_multiset: HashMap<u64, usize> = HashMap::new();
-        for line in input.lines() {
-            for star in line.split_whitespace() {
-                star_multiset
-                    .entry(star.parse::<u64>().unwrap())
-                    .and_modify(|e| *e += 1)
-                    .or_insert(1);
-            }
-        }
+        let mut star_multiset: Counter<u64> = input
+            .split_whitespace()
+            .map(|star| star.parse().unwrap())
+            .collect();
 
         for _ in 0..iterations {
-            let mut new_set = HashMap::new();
-            for (&number, &count) in star_multiset.iter() {
+            let mut new_set = Counter::new();
+            for (number, count) in star_multiset {
                 if number == 0 {
-                    new_set
-                        .entry(1)
-                        .and_modify(|e| *e += count)
-                        .or_insert(count);
+                    new_set.add_n(1, count);
                     continue;
                 }
 
-                let num_digits = number.ilog10() + 1;
-                if num_digits % 2 == 0 {
-                    let upper = number / 10u64.pow(num_digits / 2);
-                    new_set
-                        .entry(upper)
-                        .and_modify(|e| *e += count)
-                        .or_insert(count);
-                    let lower = number % 10u64.pow(num_digits / 2);
-                    new_set
-                        .entry(lower)
-                        .and_modify(|e| *e += count)
-                        .or_insert(count);
+                let num_digits = num::num_digits(number);
+                if num_digits.is_multiple_of(2) {
+                    let (upper, lower) = num::split_digits(number, num_digits / 2);
+                    new_set.add_n(upper, count);
+                    new_set.add_n(lower, count);
                     continue;
                 }
 
-                new_set
-                    .entry(number * 2024)
-                    .and_modify(|e| *e += count)
-                    .or_insert(count);
+                new_set.add_n(number * 2024, count);
             }
 
             star_multiset = new_set;
         }
 
-        let mut answer = 0;
-        for (&number, &count) in star_multiset.iter() {
-            answer += count;
+        star_multiset.total().to_string()
+    }
+
+    /// Number of stones a single stone turns into after `blinks` blinks.
+    /// Stones never interact, so each (stone, blinks) pair is only counted
+    /// once.
+    fn count_stones(memo: &mut Memo<(u64, usize), usize>, stone: u64, blinks: usize) -> usize {
+        if blinks == 0 {
+            return 1;
         }
 
-        answer.to_string()
+        memo.get_or_insert_with((stone, blinks), |memo| {
+            if stone == 0 {
+                return Self::count_stones(memo, 1, blinks - 1);
+            }
+
+            let num_digits = num::num_digits(stone);
+            if num_digits.is_multiple_of(2) {
+                let (upper, lower) = num::split_digits(stone, num_digits / 2);
+                return Self::count_stones(memo, upper, blinks - 1)
+                    + Self::count_stones(memo, lower, blinks - 1);
+            }
+
+            Self::count_stones(memo, stone * 2024, blinks - 1)
+        })
+    }
+
+    fn solve_recursive(&self, input: String, iterations: usize) -> String {
+        let mut memo = Memo::new();
+        input
+            .split_whitespace()
+            .map(|star| Self::count_stones(&mut memo, star.parse().unwrap(), iterations))
+            .sum::<usize>()
+            .to_string()
     }
 }
 
@@ -69,7 +78,7 @@ impl Solution for Sln {
     }
 
     fn part_2(&self, input: String) -> String {
-        self.solve(input, 75).to_string()
+        self.solve_recursive(input, 75)
     }
 }
 
@@ -82,4 +91,21 @@ mod tests {
         let input = r#"125 17"#;
         assert_eq!("55312", Sln::new().part_1(input.to_string()));
     }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"125 17"#;
+        assert_eq!("65601038650482", Sln::new().part_2(input.to_string()));
+    }
+
+    #[test]
+    fn test_recursive_matches_multiset() {
+        let input = "0 1 10 99 999 125 17";
+        for blinks in [0, 1, 6, 25, 40] {
+            assert_eq!(
+                Sln::new().solve(input.to_string(), blinks),
+                Sln::new().solve_recursive(input.to_string(), blinks)
+            );
+        }
+    }
 }
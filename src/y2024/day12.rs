@@ -1,6 +1,5 @@
 use crate::utils::template::Solution;
-use crate::utils::Point;
-use itertools::Itertools;
+use crate::utils::{dsu, geometry, Point};
 use std::collections::HashSet;
 
 pub struct Sln {}
@@ -10,105 +9,26 @@ impl Sln {
         Sln {}
     }
 
-    fn lookup(map: &Vec<Vec<char>>, p: Point) -> Option<&char> {
-        map.get(p.y).and_then(|row| row.get(p.x))
-    }
-
-    fn find_area(map: &Vec<Vec<char>>, start: Point, area: &mut HashSet<Point>) {
-        if !area.insert(start) {
-            return;
-        }
-
-        if Self::lookup(map, start) == Self::lookup(map, start.left()) {
-            Self::find_area(map, start.left(), area);
-        }
-        if Self::lookup(map, start) == Self::lookup(map, start.right()) {
-            Self::find_area(map, start.right(), area);
-        }
-        if Self::lookup(map, start) == Self::lookup(map, start.up()) {
-            Self::find_area(map, start.up(), area);
-        }
-        if Self::lookup(map, start) == Self::lookup(map, start.down()) {
-            Self::find_area(map, start.down(), area);
-        }
+    fn find_areas(input: &str) -> Vec<HashSet<Point>> {
+        let map: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        dsu::grid_regions(&map)
+            .into_iter()
+            .map(HashSet::from_iter)
+            .collect()
     }
 
     fn calculate_cost(area: &HashSet<Point>) -> usize {
-        let mut fence_count = 0;
-        // Sort left to right, then top to bottom
-        for &p in area
-            .iter()
-            .sorted_by(|p1, p2| p1.y.cmp(&p2.y).then(p1.x.cmp(&p2.x)))
-        {
-            if !area.contains(&p.left()) {
-                fence_count += 1;
-            }
-            if !area.contains(&p.right()) {
-                fence_count += 1;
-            }
-            if !area.contains(&p.up()) {
-                fence_count += 1;
-            }
-            if !area.contains(&p.down()) {
-                fence_count += 1;
-            }
-        }
-
-        area.len() * fence_count
+        geometry::area(area) * geometry::perimeter(area)
     }
 
     fn calculate_cost_discount(area: &HashSet<Point>) -> usize {
-        let mut fence_count = 0;
-        // Sort left to right, then top to bottom
-        for &p in area
-            .iter()
-            .sorted_by(|p1, p2| p1.y.cmp(&p2.y).then(p1.x.cmp(&p2.x)))
-        {
-            if !area.contains(&p.left()) {
-                if !(area.contains(&p.up()) && !area.contains(&p.up().left())) {
-                    fence_count += 1;
-                }
-            }
-            if !area.contains(&p.right()) {
-                if !(area.contains(&p.up()) && !area.contains(&p.up().right())) {
-                    fence_count += 1;
-                }
-            }
-            if !area.contains(&p.up()) {
-                if !(area.contains(&p.left()) && !area.contains(&p.left().up())) {
-                    fence_count += 1;
-                }
-            }
-            if !area.contains(&p.down()) {
-                if !(area.contains(&p.left()) && !area.contains(&p.left().down())) {
-                    fence_count += 1;
-                }
-            }
-        }
-
-        area.len() * fence_count
+        geometry::area(area) * geometry::sides(area)
     }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        let map: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-        let mut already_seen = HashSet::<Point>::new();
-        let mut areas = Vec::new();
-
-        for (y, row) in map.iter().enumerate() {
-            for (x, c) in row.iter().enumerate() {
-                let p = Point::new(x, y);
-                if already_seen.contains(&p) {
-                    continue;
-                }
-
-                let mut area = HashSet::new();
-                Self::find_area(&map, p, &mut area);
-                already_seen.extend(&area);
-                areas.push(area);
-            }
-        }
+        let areas = Self::find_areas(&input);
 
         let mut answer = 0;
         for area in areas {
@@ -118,23 +38,7 @@ impl Solution for Sln {
     }
 
     fn part_2(&self, input: String) -> String {
-        let map: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-        let mut already_seen = HashSet::<Point>::new();
-        let mut areas = Vec::new();
-
-        for (y, row) in map.iter().enumerate() {
-            for (x, c) in row.iter().enumerate() {
-                let p = Point::new(x, y);
-                if already_seen.contains(&p) {
-                    continue;
-                }
-
-                let mut area = HashSet::new();
-                Self::find_area(&map, p, &mut area);
-                already_seen.extend(&area);
-                areas.push(area);
-            }
-        }
+        let areas = Self::find_areas(&input);
 
         let mut answer = 0;
         for area in areas {
@@ -179,4 +83,16 @@ MMMISSJEEE"#;
 
         assert_eq!("1206", Sln::new().part_2(input.to_string()));
     }
+
+    #[test]
+    fn test_part_2_diagonal_regions() {
+        let input = r#"AAAAAA
+AAABBA
+AAABBA
+ABBAAA
+ABBAAA
+AAAAAA"#;
+
+        assert_eq!("368", Sln::new().part_2(input.to_string()));
+    }
 }
@@ -1,207 +1,126 @@
-use regex::Regex;
+use aoc_derive::FromPattern;
 
-use crate::utils::{template::Solution, Point};
+use crate::utils::{
+    linalg::{self, LinearSolution},
+    num, parse,
+    template::Solution,
+};
 
 pub struct Sln {}
 
+const COST_A: i128 = 3;
+const COST_B: i128 = 1;
+
 impl Sln {
     pub fn new() -> Sln {
         Sln {}
     }
+
+    fn parse_games(input: &str, prize_offset: u64) -> Vec<Game> {
+        parse::sections(input)
+            .into_iter()
+            .map(|section| {
+                let mut game = section.parse::<Game>().unwrap();
+                game.prize.x += prize_offset;
+                game.prize.y += prize_offset;
+                game
+            })
+            .collect()
+    }
+
+    /// Cheapest number of tokens to win the prize, if it can be won at all.
+    fn min_tokens(game: &Game) -> Option<u64> {
+        let (ax, ay) = (game.btn_a.x as i128, game.btn_a.y as i128);
+        let (bx, by) = (game.btn_b.x as i128, game.btn_b.y as i128);
+        let (px, py) = (game.prize.x as i128, game.prize.y as i128);
+        match linalg::solve(&[vec![ax, bx], vec![ay, by]], &[px, py]) {
+            LinearSolution::Inconsistent => None,
+            LinearSolution::Unique(x) => {
+                let a = x[0].to_integer().filter(|&a| a >= 0)?;
+                let b = x[1].to_integer().filter(|&b| b >= 0)?;
+                Some((a * COST_A + b * COST_B) as u64)
+            }
+            // The buttons are collinear (or zero), so every solution of one
+            // non-trivial equation also solves the other.
+            LinearSolution::Parametric { .. } => {
+                if ax != 0 || bx != 0 {
+                    Self::min_tokens_on_line(ax, bx, px)
+                } else if ay != 0 || by != 0 {
+                    Self::min_tokens_on_line(ay, by, py)
+                } else {
+                    Some(0)
+                }
+            }
+        }
+    }
+
+    /// Cheapest non-negative integer solution to `a * u + b * v = p`, where
+    /// `a` and `b` are non-negative and not both zero.
+    fn min_tokens_on_line(a: i128, b: i128, p: i128) -> Option<u64> {
+        let (g, x, y) = num::egcd(a as i64, b as i64);
+        let g = g as i128;
+        if p % g != 0 {
+            return None;
+        }
+
+        // All integer solutions are (u0 + k * du, v0 - k * dv)
+        let (u0, v0) = (x as i128 * (p / g), y as i128 * (p / g));
+        let (du, dv) = (b / g, a / g);
+
+        // u >= 0 and v >= 0 bound k from below and above respectively
+        let k_min = (du != 0).then(|| -u0.div_euclid(du));
+        let k_max = (dv != 0).then(|| v0.div_euclid(dv));
+        // The cost is linear in k, so the optimum is at one end of the range
+        let cost_slope = COST_A * du - COST_B * dv;
+        let k = if cost_slope < 0 { k_max? } else { k_min? };
+        let (u, v) = (u0 + k * du, v0 - k * dv);
+        if u < 0 || v < 0 {
+            return None;
+        }
+        Some((u * COST_A + v * COST_B) as u64)
+    }
+}
+
+#[derive(FromPattern)]
+#[aoc(pattern = "X+{}, Y+{}")]
+struct Button {
+    x: u64,
+    y: u64,
+}
+
+#[derive(FromPattern)]
+#[aoc(pattern = "X={}, Y={}")]
+struct Prize {
+    x: u64,
+    y: u64,
 }
 
+#[derive(FromPattern)]
+#[aoc(pattern = "Button A: {}\nButton B: {}\nPrize: {}")]
 struct Game {
-    btn_a: (u64, u64),
-    btn_b: (u64, u64),
-    prize: Point,
+    btn_a: Button,
+    btn_b: Button,
+    prize: Prize,
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        let button_a_re = Regex::new(r"Button A: X\+(\d+), Y\+(\d+)").unwrap();
-        let button_b_re = Regex::new(r"Button B: X\+(\d+), Y\+(\d+)").unwrap();
-        let prize_re = Regex::new(r"Prize: X\=(\d+), Y\=(\d+)").unwrap();
-
-        let mut games = vec![];
-        for game_text in input.split("\n\n") {
-            let button_a_matches = button_a_re.captures(game_text).unwrap();
-            let button_a_x = button_a_matches
-                .get(1)
-                .unwrap()
-                .as_str()
-                .parse::<u64>()
-                .unwrap();
-            let button_a_y = button_a_matches
-                .get(2)
-                .unwrap()
-                .as_str()
-                .parse::<u64>()
-                .unwrap();
-
-            let button_b_matches = button_b_re.captures(game_text).unwrap();
-            let button_b_x = button_b_matches
-                .get(1)
-                .unwrap()
-                .as_str()
-                .parse::<u64>()
-                .unwrap();
-            let button_b_y = button_b_matches
-                .get(2)
-                .unwrap()
-                .as_str()
-                .parse::<u64>()
-                .unwrap();
-
-            let prize_matches = prize_re.captures(game_text).unwrap();
-            let prize_x = prize_matches
-                .get(1)
-                .unwrap()
-                .as_str()
-                .parse::<usize>()
-                .unwrap();
-            let prize_y = prize_matches
-                .get(2)
-                .unwrap()
-                .as_str()
-                .parse::<usize>()
-                .unwrap();
-
-            games.push(Game {
-                btn_a: (button_a_x, button_a_y),
-                btn_b: (button_b_x, button_b_y),
-                prize: Point::new(prize_x, prize_y),
-            });
-        }
+        let games = Self::parse_games(&input, 0);
 
         let mut answer = 0;
         for game in games {
-            // x1*a11 + x2*a12 = b1
-            // x1*a21 + x2*a22 = b2
-            //
-            // x2*a22 = b2 - x1*a21
-            // x2 = (b2 - x1*a21)/a22
-            //
-            // x1*a11 + ((b2 - x1*a21)/a22)*a12 = b1
-            // x1*a11 + (b2/a22 - x1*a21/a22)*a12 = b1
-            // x1*a11 + a12*b2/a22 - a12*x1*a21/a22 = b1
-            // x1*a11 - x1*a12*a21/a22 = b1 - a12*b2/a22
-            // x1(a11 - a12*a21/a22) = b1 - a12*b2/a22
-            // x1 = (b1 - a12*b2/a22) / (a11 - a12*a21/a22)
-            // assume no linear dependence and see how it goes...
-
-            let a11 = game.btn_a.0 as f64;
-            let a12 = game.btn_b.0 as f64;
-            let a21 = game.btn_a.1 as f64;
-            let a22 = game.btn_b.1 as f64;
-            let b1 = game.prize.x as f64;
-            let b2 = game.prize.y as f64;
-
-            let x1 = (b1 - a12 * b2 / a22) / (a11 - a12 * a21 / a22);
-            let x2 = (b2 - x1 * a21) / a22;
-
-            let x1_round = x1.round() as u64;
-            let x2_round = x2.round() as u64;
-
-            if x1_round * game.btn_a.0 + x2_round * game.btn_b.0 == game.prize.x as u64
-                && x1_round * game.btn_a.1 + x2_round * game.btn_b.1 == game.prize.y as u64
-            {
-                answer += x1_round * 3 + x2_round;
-            }
+            answer += Self::min_tokens(&game).unwrap_or(0);
         }
 
         answer.to_string()
     }
 
     fn part_2(&self, input: String) -> String {
-        let button_a_re = Regex::new(r"Button A: X\+(\d+), Y\+(\d+)").unwrap();
-        let button_b_re = Regex::new(r"Button B: X\+(\d+), Y\+(\d+)").unwrap();
-        let prize_re = Regex::new(r"Prize: X\=(\d+), Y\=(\d+)").unwrap();
-
-        let mut games = vec![];
-        for game_text in input.split("\n\n") {
-            let button_a_matches = button_a_re.captures(game_text).unwrap();
-            let button_a_x = button_a_matches
-                .get(1)
-                .unwrap()
-                .as_str()
-                .parse::<u64>()
-                .unwrap();
-            let button_a_y = button_a_matches
-                .get(2)
-                .unwrap()
-                .as_str()
-                .parse::<u64>()
-                .unwrap();
-
-            let button_b_matches = button_b_re.captures(game_text).unwrap();
-            let button_b_x = button_b_matches
-                .get(1)
-                .unwrap()
-                .as_str()
-                .parse::<u64>()
-                .unwrap();
-            let button_b_y = button_b_matches
-                .get(2)
-                .unwrap()
-                .as_str()
-                .parse::<u64>()
-                .unwrap();
-
-            let prize_matches = prize_re.captures(game_text).unwrap();
-            let prize_x = prize_matches
-                .get(1)
-                .unwrap()
-                .as_str()
-                .parse::<usize>()
-                .unwrap();
-            let prize_y = prize_matches
-                .get(2)
-                .unwrap()
-                .as_str()
-                .parse::<usize>()
-                .unwrap();
-
-            games.push(Game {
-                btn_a: (button_a_x, button_a_y),
-                btn_b: (button_b_x, button_b_y),
-                prize: Point::new(prize_x + 10000000000000, prize_y + 10000000000000),
-            });
-        }
+        let games = Self::parse_games(&input, 10000000000000);
 
         let mut answer = 0u64;
         for game in games {
-            // x1*a11 + x2*a12 = b1
-            // x1*a21 + x2*a22 = b2
-            //
-            // x2*a22 = b2 - x1*a21
-            // x2 = (b2 - x1*a21)/a22
-            //
-            // x1*a11 + ((b2 - x1*a21)/a22)*a12 = b1
-            // x1*a11 + (b2/a22 - x1*a21/a22)*a12 = b1
-            // x1*a11 + a12*b2/a22 - a12*x1*a21/a22 = b1
-            // x1*a11 - x1*a12*a21/a22 = b1 - a12*b2/a22
-            // x1(a11 - a12*a21/a22) = b1 - a12*b2/a22
-            // x1 = (b1 - a12*b2/a22) / (a11 - a12*a21/a22)
-            // assume no linear dependence and see how it goes...
-
-            let a11 = game.btn_a.0 as f64;
-            let a12 = game.btn_b.0 as f64;
-            let a21 = game.btn_a.1 as f64;
-            let a22 = game.btn_b.1 as f64;
-            let b1 = game.prize.x as f64;
-            let b2 = game.prize.y as f64;
-
-            let x1 = (b1 - a12 * b2 / a22) / (a11 - a12 * a21 / a22);
-            let x2 = (b2 - x1 * a21) / a22;
-
-            let x1_round = x1.round() as u64;
-            let x2_round = x2.round() as u64;
-
-            if x1_round * game.btn_a.0 + x2_round * game.btn_b.0 == game.prize.x as u64
-                && x1_round * game.btn_a.1 + x2_round * game.btn_b.1 == game.prize.y as u64
-            {
-                answer += x1_round * 3 + x2_round;
-            }
+            answer += Self::min_tokens(&game).unwrap_or(0);
         }
 
         answer.to_string()
@@ -232,4 +151,86 @@ Prize: X=18641, Y=10279"#;
 
         assert_eq!("480", Sln::new().part_1(input.to_string()));
     }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279"#;
+
+        assert_eq!("875318608908", Sln::new().part_2(input.to_string()));
+    }
+
+    #[test]
+    fn test_crlf_input() {
+        let input = "Button A: X+94, Y+34\r\nButton B: X+22, Y+67\r\nPrize: X=8400, Y=5400\r\n\r\nButton A: X+26, Y+66\r\nButton B: X+67, Y+21\r\nPrize: X=12748, Y=12176\r\n";
+        assert_eq!("280", Sln::new().part_1(input.to_string()));
+        assert!(
+            "Button A: X+1, Y+2\r\nButton B: X+3, Y+4\r\nPrize: X=5, Y=6"
+                .parse::<Game>()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_fields_named_like_generated_locals() {
+        #[derive(Debug, PartialEq, FromPattern)]
+        #[aoc(pattern = "{} at {}: {}")]
+        struct Locals {
+            s: String,
+            pos: usize,
+            #[aoc(sep = ",")]
+            items: Vec<u64>,
+        }
+
+        assert_eq!(
+            Ok(Locals {
+                s: "ab".to_string(),
+                pos: 3,
+                items: vec![1, 2],
+            }),
+            "ab at 3: 1,2".parse::<Locals>()
+        );
+        assert_eq!(
+            Err(LocalsParseError::InvalidField {
+                field: "items",
+                value: "x".to_string(),
+                column: 12,
+            }),
+            "ab at 3: 1,x".parse::<Locals>()
+        );
+    }
+
+    #[test]
+    fn test_collinear_buttons() {
+        let input = r#"Button A: X+4, Y+4
+Button B: X+1, Y+1
+Prize: X=10, Y=10
+
+Button A: X+2, Y+2
+Button B: X+1, Y+1
+Prize: X=10, Y=10
+
+Button A: X+1, Y+2
+Button B: X+2, Y+4
+Prize: X=3, Y=7
+
+Button A: X+6, Y+3
+Button B: X+4, Y+2
+Prize: X=10, Y=5"#;
+
+        assert_eq!("22", Sln::new().part_1(input.to_string()));
+    }
 }
@@ -1,8 +1,11 @@
-use std::collections::HashMap;
+use std::io;
 
-use regex::Regex;
-
-use crate::utils::{template::Solution, Point};
+use crate::utils::{
+    cycle, num, parse,
+    template::{self, Solution},
+    viz::{Canvas, Visualizer},
+    Counter, Point,
+};
 
 pub struct Sln {}
 
@@ -10,9 +13,93 @@ impl Sln {
     pub fn new() -> Sln {
         Sln {}
     }
+
+    fn parse_states(input: &str) -> Vec<State> {
+        parse::int_rows::<4, isize>(input)
+            .unwrap()
+            .into_iter()
+            .map(|[px, py, vx, vy]| State {
+                position: Point::new(px as usize, py as usize),
+                velocity: (vx, vy),
+            })
+            .collect()
+    }
+
+    /// Finds the step in `0..period` where the given one-dimensional
+    /// `(position, velocity)` pairs, wrapping at `modulus`, have the smallest
+    /// variance.
+    fn min_variance_step(
+        axis: impl Iterator<Item = (usize, isize)> + Clone,
+        modulus: usize,
+        period: usize,
+    ) -> usize {
+        let mut best = (f64::MAX, 0);
+        for step in 0..period {
+            let mut sum = 0.0;
+            let mut sum_sq = 0.0;
+            let mut n = 0.0;
+            for (p, v) in axis.clone() {
+                let p = (p as isize + v * step as isize).rem_euclid(modulus as isize) as f64;
+                sum += p;
+                sum_sq += p * p;
+                n += 1.0;
+            }
+            let variance = sum_sq / n - (sum / n).powf(2.0);
+            if variance < best.0 {
+                best = (variance, step);
+            }
+        }
+
+        best.1
+    }
+
+    /// Number of steps after which the given one-dimensional
+    /// `(position, velocity)` pairs are all back where they started. This is
+    /// `modulus` unless every velocity shares a factor with it.
+    fn axis_period(axis: &[(usize, isize)], modulus: usize) -> usize {
+        let positions: Vec<usize> = axis.iter().map(|&(p, _)| p).collect();
+        let step = |positions: &Vec<usize>| -> Vec<usize> {
+            positions
+                .iter()
+                .zip(axis)
+                .map(|(&p, &(_, v))| (p as isize + v).rem_euclid(modulus as isize) as usize)
+                .collect()
+        };
+        // Every robot moves in a permutation, so the cycle starts at step 0
+        cycle::detect(positions, step).1
+    }
+
+    /// The step where the robots form the tree.
+    fn tree_step(states: &[State]) -> Option<usize> {
+        // The x coordinates repeat periodically, as do the y coordinates. The
+        // tree is where the robots cluster on both axes, so find the least
+        // spread out step in each axis' period and combine them with the CRT.
+        let xs: Vec<(usize, isize)> = states
+            .iter()
+            .map(|s| (s.position.x, s.velocity.0))
+            .collect();
+        let ys: Vec<(usize, isize)> = states
+            .iter()
+            .map(|s| (s.position.y, s.velocity.1))
+            .collect();
+        let (x_period, y_period) = (
+            Self::axis_period(&xs, WIDTH),
+            Self::axis_period(&ys, HEIGHT),
+        );
+        let x_step = Self::min_variance_step(xs.iter().copied(), WIDTH, x_period);
+        let y_step = Self::min_variance_step(ys.iter().copied(), HEIGHT, y_period);
+        num::crt(&[
+            (x_step as i64, x_period as i64),
+            (y_step as i64, y_period as i64),
+        ])
+        .map(|(step, _)| step as usize)
+    }
 }
 
-#[derive(Debug)]
+const WIDTH: usize = 101;
+const HEIGHT: usize = 103;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct State {
     position: Point,
     velocity: (isize, isize),
@@ -22,54 +109,49 @@ impl State {
     fn update(&self) -> State {
         State {
             position: Point::new(
-                (self.position.x as isize + self.velocity.0).rem_euclid(101) as usize,
-                (self.position.y as isize + self.velocity.1).rem_euclid(103) as usize,
+                (self.position.x as isize + self.velocity.0).rem_euclid(WIDTH as isize) as usize,
+                (self.position.y as isize + self.velocity.1).rem_euclid(HEIGHT as isize) as usize,
             ),
             velocity: self.velocity,
         }
     }
+
+    fn position_after(&self, steps: usize) -> Point {
+        let steps = steps as isize;
+        Point::new(
+            (self.position.x as isize + self.velocity.0 * steps).rem_euclid(WIDTH as isize)
+                as usize,
+            (self.position.y as isize + self.velocity.1 * steps).rem_euclid(HEIGHT as isize)
+                as usize,
+        )
+    }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        let mut states: Vec<State> = vec![];
-        for line in input.lines() {
-            let r = Regex::new(r"^p=(\d+),(\d+) v=(-?\d+),(-?\d+)$").unwrap();
-            let c = r.captures(line).unwrap();
-            let state = State {
-                position: Point::new(
-                    c.get(1).unwrap().as_str().parse::<usize>().unwrap(),
-                    c.get(2).unwrap().as_str().parse::<usize>().unwrap(),
-                ),
-                velocity: (
-                    c.get(3).unwrap().as_str().parse::<isize>().unwrap(),
-                    c.get(4).unwrap().as_str().parse::<isize>().unwrap(),
-                ),
-            };
-            states.push(state);
-        }
-
-        for _ in 0..100 {
-            states = states.iter().map(|s| s.update()).collect();
-        }
+        let states = cycle::extrapolate(
+            Self::parse_states(&input),
+            |states: &Vec<State>| states.iter().map(State::update).collect(),
+            100,
+        );
 
-        let mut qs = HashMap::new();
+        let mut qs = Counter::new();
         for state in states {
             let p = state.position;
-            if p.x == 50 || p.y == 51 {
+            if p.x == WIDTH / 2 || p.y == HEIGHT / 2 {
                 continue;
             }
 
             let q = match p.x {
-                x if x < 50 => {
-                    if p.y < 51 {
+                x if x < WIDTH / 2 => {
+                    if p.y < HEIGHT / 2 {
                         1
                     } else {
                         3
                     }
                 }
-                x if x > 50 => {
-                    if p.y < 51 {
+                x if x > WIDTH / 2 => {
+                    if p.y < HEIGHT / 2 {
                         2
                     } else {
                         4
@@ -78,56 +160,65 @@ impl Solution for Sln {
                 _ => 0,
             };
 
-            qs.entry(q).and_modify(|c| *c += 1).or_insert(1);
+            qs.add(q);
         }
 
-        (qs[&1] * qs[&2] * qs[&3] * qs[&4]).to_string()
+        (qs.get(&1) * qs.get(&2) * qs.get(&3) * qs.get(&4)).to_string()
     }
 
     fn part_2(&self, input: String) -> String {
-        let mut states: Vec<State> = vec![];
-        for line in input.lines() {
-            let r = Regex::new(r"^p=(\d+),(\d+) v=(-?\d+),(-?\d+)$").unwrap();
-            let c = r.captures(line).unwrap();
-            let state = State {
-                position: Point::new(
-                    c.get(1).unwrap().as_str().parse::<usize>().unwrap(),
-                    c.get(2).unwrap().as_str().parse::<usize>().unwrap(),
-                ),
-                velocity: (
-                    c.get(3).unwrap().as_str().parse::<isize>().unwrap(),
-                    c.get(4).unwrap().as_str().parse::<isize>().unwrap(),
-                ),
-            };
-            states.push(state);
+        let states = Self::parse_states(&input);
+        match Self::tree_step(&states) {
+            Some(step) => step.to_string(),
+            None => template::unsolved("X-Mas tree not found"),
         }
+    }
 
-        for i in 0..10000 {
-            states = states.iter().map(|s| s.update()).collect();
-            let mut sum_x = 0.0;
-            let mut sum_x2 = 0.0;
-            let mut sum_y = 0.0;
-            let mut sum_y2 = 0.0;
-            for state in states.iter() {
-                let p = state.position;
-                sum_x += p.x as f64;
-                sum_x2 += (p.x as f64).powf(2.0);
-                sum_y += p.y as f64;
-                sum_y2 += (p.y as f64).powf(2.0);
-            }
+    fn visualize(&self, input: String, viz: &mut Visualizer) -> io::Result<bool> {
+        let states = Self::parse_states(&input);
+        let steps = [Some(100), Self::tree_step(&states)];
+        for step in steps.into_iter().flatten() {
+            let positions: Vec<Point> = states.iter().map(|s| s.position_after(step)).collect();
+            viz.compact_frame(&Canvas::from_points(
+                &positions,
+                WIDTH,
+                HEIGHT,
+                Some([0, 200, 0]),
+            ))?;
+        }
 
-            let n = states.len() as f64;
-            let mean_x = sum_x / n;
-            let mean_x2 = sum_x2 / n;
-            let mean_y = sum_y / n;
-            let mean_y2 = sum_y2 / n;
-            let var_x = mean_x2 - mean_x.powf(2.0);
-            let var_y = mean_y2 - mean_y.powf(2.0);
-            if var_x < 600.0 && var_y < 600.0 {
-                return (i + 1).to_string();
-            }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axis_period() {
+        assert_eq!(WIDTH, Sln::axis_period(&[(3, 1), (5, -2)], WIDTH));
+        assert_eq!(1, Sln::axis_period(&[(3, 0), (5, 0)], WIDTH));
+        assert_eq!(3, Sln::axis_period(&[(1, 2), (0, 4)], 6));
+    }
+
+    #[test]
+    fn test_part_2_finds_clustered_step() {
+        // Robots that all end up in a 5x5 square after 7777 steps, with
+        // pseudo-random velocities.
+        let step = 7777;
+        let mut seed = 12345isize;
+        let mut input = String::new();
+        for i in 0..200 {
+            seed = (seed * 1103515245 + 12345).rem_euclid(1 << 31);
+            let vx = seed % 201 - 100;
+            let vy = (seed / 201) % 201 - 100;
+            let (tx, ty) = (48 + i % 5, 50 + (i / 5) % 5);
+            let px = (tx - vx * step).rem_euclid(WIDTH as isize);
+            let py = (ty - vy * step).rem_euclid(HEIGHT as isize);
+            input += &format!("p={},{} v={},{}\n", px, py, vx, vy);
         }
 
-        "X-Mas tree not found - adjust variance target if necessary".to_string()
+        assert_eq!("7777", Sln::new().part_2(input));
     }
 }
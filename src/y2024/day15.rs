@@ -1,16 +1,60 @@
 use std::{
     collections::{HashSet, VecDeque},
     fmt::Display,
+    io,
 };
 
-use crate::utils::{template::Solution, Point};
+use crate::utils::{
+    sim::{Debugger, Runner, Simulation},
+    template::Solution,
+    viz::{Canvas, Visualizer},
+    Point,
+};
 
 pub struct Sln {}
 
+/// Most frames `visualize` shows, however many moves the robot makes.
+const MAX_FRAMES: usize = 500;
+
 impl Sln {
     pub fn new() -> Sln {
         Sln {}
     }
+
+    /// Reads the map and the robot's movements. A `wide` map has every tile
+    /// doubled, with boxes becoming `[]`.
+    fn parse(input: &str, wide: bool) -> Warehouse {
+        let mut split = input.split("\n\n");
+        let mut map_vec: Vec<Vec<char>> = vec![];
+        for line in split.next().unwrap().lines() {
+            if !wide {
+                map_vec.push(line.chars().collect());
+                continue;
+            }
+
+            let mut row: Vec<char> = vec![];
+            for col in line.chars() {
+                let (c1, c2) = match col {
+                    '#' => ('#', '#'),
+                    '@' => ('@', '.'),
+                    'O' => ('[', ']'),
+                    '.' => ('.', '.'),
+                    _ => panic!(),
+                };
+                row.push(c1);
+                row.push(c2);
+            }
+            map_vec.push(row);
+        }
+        let movements: Vec<Movement> = split
+            .next()
+            .unwrap()
+            .chars()
+            .filter_map(|c| c.try_into().ok())
+            .collect();
+
+        Warehouse::new(Map::new(map_vec), movements)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -71,17 +115,14 @@ impl From<MapTile> for char {
     }
 }
 
+#[derive(Clone)]
 struct Map {
     m: Vec<Vec<char>>,
-    max_x: usize,
-    max_y: usize,
 }
 
 impl Map {
     fn new(m: Vec<Vec<char>>) -> Map {
-        let max_x = m.first().unwrap().len();
-        let max_y = m.len();
-        Map { m, max_x, max_y }
+        Map { m }
     }
 
     fn get_tile(&self, p: Point) -> MapTile {
@@ -98,20 +139,17 @@ impl Map {
     }
 
     fn can_push(&self, p: Point, m: Movement) -> bool {
-        if self.get_tile(p) == MapTile::Empty {
-            return true;
-        } else if self.get_tile(p) == MapTile::Wall {
-            return false;
-        } else if self.get_tile(p) == MapTile::LargeObjectLeft
-            && (m == Movement::Up || m == Movement::Down)
-        {
-            return self.can_push(m.apply(p), m) && self.can_push(m.apply(p.right()), m);
-        } else if self.get_tile(p) == MapTile::LargeObjectRight
-            && (m == Movement::Up || m == Movement::Down)
-        {
-            return self.can_push(m.apply(p), m) && self.can_push(m.apply(p.left()), m);
-        } else {
-            return self.can_push(m.apply(p), m);
+        let vertical = m == Movement::Up || m == Movement::Down;
+        match self.get_tile(p) {
+            MapTile::Empty => true,
+            MapTile::Wall => false,
+            MapTile::LargeObjectLeft if vertical => {
+                self.can_push(m.apply(p), m) && self.can_push(m.apply(p.right()), m)
+            }
+            MapTile::LargeObjectRight if vertical => {
+                self.can_push(m.apply(p), m) && self.can_push(m.apply(p.left()), m)
+            }
+            _ => self.can_push(m.apply(p), m),
         }
     }
 
@@ -124,7 +162,7 @@ impl Map {
                 continue;
             }
             let curr_t = self.get_tile(p);
-            self.write(p, t.into());
+            self.write(p, t);
             if curr_t == MapTile::Empty {
                 continue;
             }
@@ -140,19 +178,121 @@ impl Map {
     }
 }
 
+impl Map {
+    fn robot(&self) -> Point {
+        for (y, r) in self.m.iter().enumerate() {
+            for (x, &c) in r.iter().enumerate() {
+                if c == '@' {
+                    return Point::new(x, y);
+                }
+            }
+        }
+
+        panic!("No robot on the map")
+    }
+
+    /// Moves the robot at `robot_pos` if nothing blocks it and returns its new
+    /// position.
+    fn move_robot(&mut self, robot_pos: Point, movement: Movement) -> Point {
+        if !self.can_push(robot_pos, movement) {
+            return robot_pos;
+        }
+
+        self.push(MapTile::Empty, robot_pos, movement);
+        movement.apply(robot_pos)
+    }
+
+    /// Sum of the GPS coordinates of every `tile`.
+    fn gps_sum(&self, tile: char) -> usize {
+        let mut answer = 0;
+        for (y, r) in self.m.iter().enumerate() {
+            for (x, &c) in r.iter().enumerate() {
+                if c == tile {
+                    answer += x + 100 * y;
+                }
+            }
+        }
+
+        answer
+    }
+
+    fn canvas(&self) -> Canvas {
+        let mut canvas = Canvas::from_chars(&self.m);
+        for (y, r) in self.m.iter().enumerate() {
+            for (x, &c) in r.iter().enumerate() {
+                let color = match MapTile::try_from(c) {
+                    Ok(MapTile::Wall) => [128, 128, 128],
+                    Ok(MapTile::Robot) => [255, 64, 64],
+                    Ok(MapTile::Object | MapTile::LargeObjectLeft | MapTile::LargeObjectRight) => {
+                        [230, 160, 40]
+                    }
+                    _ => continue,
+                };
+                canvas.set(Point::new(x, y), c, Some(color));
+            }
+        }
+
+        canvas
+    }
+}
+
 impl Display for Map {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for r in self.m.iter() {
             for c in r {
                 write!(f, "{}", c)?;
             }
-            writeln!(f, "")?;
+            writeln!(f)?;
         }
 
         Ok(())
     }
 }
 
+/// The robot working through its list of movements, one per step.
+struct Warehouse {
+    map: Map,
+    robot: Point,
+    movements: Vec<Movement>,
+    next: usize,
+}
+
+impl Warehouse {
+    fn new(map: Map, movements: Vec<Movement>) -> Warehouse {
+        Warehouse {
+            robot: map.robot(),
+            map,
+            movements,
+            next: 0,
+        }
+    }
+}
+
+impl Simulation for Warehouse {
+    type Snapshot = (Map, Point, usize);
+
+    fn step(&mut self) -> bool {
+        let Some(&movement) = self.movements.get(self.next) else {
+            return false;
+        };
+        self.robot = self.map.move_robot(self.robot, movement);
+        self.next += 1;
+        true
+    }
+
+    fn snapshot(&self) -> Self::Snapshot {
+        (self.map.clone(), self.robot, self.next)
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        (self.map, self.robot, self.next) = snapshot;
+    }
+
+    fn canvas(&self) -> Canvas {
+        self.map.canvas()
+    }
+}
+
 impl TryFrom<char> for Movement {
     type Error = ();
 
@@ -169,108 +309,134 @@ impl TryFrom<char> for Movement {
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        let mut split = input.split("\n\n");
-        let mut map: Map = Map::new(
-            split
-                .next()
-                .unwrap()
-                .lines()
-                .map(|l| l.chars().collect())
-                .collect(),
-        );
-        let movements: Vec<Movement> = split
-            .next()
-            .unwrap()
-            .chars()
-            .filter_map(|c| c.try_into().ok())
-            .collect();
+        let mut warehouse = Self::parse(&input, false);
+        while warehouse.step() {}
 
-        let mut robot_pos = Point::new(0, 0);
-        for (y, r) in map.m.iter().enumerate() {
-            for (x, &c) in r.iter().enumerate() {
-                if c == '@' {
-                    robot_pos = Point::new(x, y);
-                }
-            }
-        }
+        warehouse.map.gps_sum('O').to_string()
+    }
 
-        for movement in movements {
-            if map.can_push(robot_pos, movement) {
-                map.push(MapTile::Empty, robot_pos, movement);
-                robot_pos = movement.apply(robot_pos);
-            }
-        }
+    fn part_2(&self, input: String) -> String {
+        let mut warehouse = Self::parse(&input, true);
+        while warehouse.step() {}
 
-        let mut answer = 0;
-        for (y, r) in map.m.iter().enumerate() {
-            for (x, &c) in r.iter().enumerate() {
-                if c == 'O' {
-                    answer += x + 100 * y;
-                }
+        warehouse.map.gps_sum('[').to_string()
+    }
+
+    fn visualize(&self, input: String, viz: &mut Visualizer) -> io::Result<bool> {
+        let mut warehouse = Self::parse(&input, true);
+        // Real inputs have around 20k moves, so only show every `stride`th one
+        let stride = warehouse.movements.len().div_ceil(MAX_FRAMES).max(1);
+        viz.frame(&warehouse.canvas())?;
+        while warehouse.step() {
+            if warehouse.next.is_multiple_of(stride) || warehouse.next == warehouse.movements.len()
+            {
+                viz.frame(&warehouse.canvas())?;
             }
         }
 
-        answer.to_string()
+        Ok(true)
     }
 
-    fn part_2(&self, input: String) -> String {
-        let mut split = input.split("\n\n");
-        let mut map_vec: Vec<Vec<char>> = vec![];
-        for line in split.next().unwrap().lines() {
-            let mut row: Vec<char> = vec![];
-            for col in line.chars() {
-                let (c1, c2) = match col {
-                    '#' => ('#', '#'),
-                    '@' => ('@', '.'),
-                    'O' => ('[', ']'),
-                    '.' => ('.', '.'),
-                    _ => panic!(),
-                };
-                row.push(c1);
-                row.push(c2);
-            }
-            map_vec.push(row);
-        }
-        let mut map: Map = Map::new(map_vec);
-        let movements: Vec<Movement> = split
-            .next()
-            .unwrap()
-            .chars()
-            .filter_map(|c| c.try_into().ok())
-            .collect();
+    fn step_through(&self, input: String) -> Option<Box<dyn Debugger>> {
+        Some(Box::new(Runner::new(Self::parse(&input, true))))
+    }
+}
 
-        let mut robot_pos = Point::new(0, 0);
-        for (y, r) in map.m.iter().enumerate() {
-            for (x, &c) in r.iter().enumerate() {
-                if c == '@' {
-                    robot_pos = Point::new(x, y);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
+
+    /// A small walled warehouse with random walls, boxes, robot position and
+    /// movements. Part 2 turns its boxes into wide ones.
+    #[derive(Debug, Clone)]
+    struct WarehouseInput(String);
+
+    impl Arbitrary for WarehouseInput {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let width = usize::arbitrary(g) % 7 + 4;
+            let height = usize::arbitrary(g) % 6 + 3;
+            let mut map = vec![vec!['#'; width + 2]; height + 2];
+            for row in map[1..=height].iter_mut() {
+                for cell in row[1..=width].iter_mut() {
+                    *cell = *g.choose(&['.', '.', '.', 'O', 'O', '#']).unwrap();
                 }
             }
+            let robot = (
+                usize::arbitrary(g) % height + 1,
+                usize::arbitrary(g) % width + 1,
+            );
+            map[robot.0][robot.1] = '@';
+
+            let moves: String = (0..usize::arbitrary(g) % 60)
+                .map(|_| *g.choose(&['^', 'v', '<', '>']).unwrap())
+                .collect();
+            let map: Vec<String> = map.into_iter().map(String::from_iter).collect();
+            WarehouseInput(format!("{}\n\n{}", map.join("\n"), moves))
         }
+    }
 
-        for movement in movements {
-            if map.can_push(robot_pos, movement) {
-                map.push(MapTile::Empty, robot_pos, movement);
-                robot_pos = movement.apply(robot_pos);
+    /// Collects everything in the way of each move before moving any of it.
+    fn brute_force_part_2(input: &str) -> usize {
+        let mut warehouse = Sln::parse(input, true);
+        let grid = &mut warehouse.map.m;
+        let (mut y, mut x) = (warehouse.robot.y, warehouse.robot.x);
+        for movement in warehouse.movements {
+            let (dy, dx): (isize, isize) = match movement {
+                Movement::Up => (-1, 0),
+                Movement::Down => (1, 0),
+                Movement::Left => (0, -1),
+                Movement::Right => (0, 1),
+            };
+            let shift =
+                |(y, x): (usize, usize)| ((y as isize + dy) as usize, (x as isize + dx) as usize);
+
+            let mut to_move = vec![(y, x)];
+            let mut queue = vec![(y, x)];
+            let mut blocked = false;
+            while let Some(p) = queue.pop() {
+                let (ny, nx) = shift(p);
+                let mut next = vec![];
+                match grid[ny][nx] {
+                    '#' => blocked = true,
+                    '[' if dy != 0 => next.extend([(ny, nx), (ny, nx + 1)]),
+                    ']' if dy != 0 => next.extend([(ny, nx), (ny, nx - 1)]),
+                    '[' | ']' => next.push((ny, nx)),
+                    _ => {}
+                }
+                for n in next {
+                    if !to_move.contains(&n) {
+                        to_move.push(n);
+                        queue.push(n);
+                    }
+                }
+            }
+            if blocked {
+                continue;
             }
-        }
 
-        let mut answer = 0;
-        for (y, r) in map.m.iter().enumerate() {
-            for (x, &c) in r.iter().enumerate() {
-                if c == '[' {
-                    answer += x + 100 * y;
-                }
+            let tiles: Vec<char> = to_move.iter().map(|&(y, x)| grid[y][x]).collect();
+            for &(y, x) in to_move.iter() {
+                grid[y][x] = '.';
+            }
+            for (&p, t) in to_move.iter().zip(tiles) {
+                let (ny, nx) = shift(p);
+                grid[ny][nx] = t;
             }
+            (y, x) = shift((y, x));
         }
 
-        answer.to_string()
+        warehouse.map.gps_sum('[')
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[quickcheck]
+    fn test_part_2_matches_brute_force(input: WarehouseInput) {
+        assert_eq!(
+            brute_force_part_2(&input.0).to_string(),
+            Sln::new().part_2(input.0)
+        );
+    }
 
     #[test]
     fn test_part_1_small() {
@@ -338,4 +504,28 @@ vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
 v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^"#;
         assert_eq!("9021", Sln::new().part_2(input.to_string()));
     }
+
+    #[test]
+    fn test_step_back_restores_map() {
+        let input = r#"#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vv<<^^<<^^"#;
+        let mut runner = Runner::with_interval(Sln::parse(input, true), 4);
+        let mut maps = vec![runner.sim().map.to_string()];
+        while runner.forward() {
+            maps.push(runner.sim().map.to_string());
+        }
+        assert_eq!(11, runner.current_step());
+
+        for n in [9, 4, 5, 0, 11] {
+            runner.jump_to(n);
+            assert_eq!(maps[n], runner.sim().map.to_string());
+        }
+    }
 }
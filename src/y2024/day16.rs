@@ -1,7 +1,132 @@
-use crate::utils::template::Solution;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::utils::{
+    parse,
+    template::{self, Solution},
+    Point,
+};
 
 pub struct Sln {}
 
+type Cost = u64;
+
+const MOVE_COST: Cost = 1;
+const TURN_COST: Cost = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    fn apply(&self, p: Point) -> Point {
+        match self {
+            Direction::North => p.up(),
+            Direction::East => p.right(),
+            Direction::South => p.down(),
+            Direction::West => p.left(),
+        }
+    }
+
+    fn turns(&self) -> [Direction; 2] {
+        match self {
+            Direction::North | Direction::South => [Direction::East, Direction::West],
+            Direction::East | Direction::West => [Direction::North, Direction::South],
+        }
+    }
+
+    fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+}
+
+type State = (Point, Direction);
+
+struct Maze {
+    grid: Vec<Vec<char>>,
+    start: Point,
+    end: Point,
+}
+
+impl Maze {
+    fn parse(input: &str) -> Maze {
+        let grid = parse::char_grid(input);
+        let find = |target: char| {
+            grid.iter()
+                .enumerate()
+                .find_map(|(y, row)| {
+                    row.iter()
+                        .position(|&c| c == target)
+                        .map(|x| Point::new(x, y))
+                })
+                .unwrap()
+        };
+        let (start, end) = (find('S'), find('E'));
+        Maze { grid, start, end }
+    }
+
+    fn is_open(&self, p: Point) -> bool {
+        self.grid
+            .get(p.y)
+            .and_then(|row| row.get(p.x))
+            .is_some_and(|&c| c != '#')
+    }
+
+    /// Lowest cost to reach every state from any of `starts`. Moving forward
+    /// one tile and turning 90 degrees on the spot are the only moves.
+    fn lowest_costs(&self, starts: &[State]) -> HashMap<State, Cost> {
+        let mut costs = HashMap::new();
+        let mut queue: BinaryHeap<Reverse<(Cost, Point, Direction)>> =
+            starts.iter().map(|&(p, d)| Reverse((0, p, d))).collect();
+        while let Some(Reverse((cost, p, d))) = queue.pop() {
+            if costs.contains_key(&(p, d)) {
+                continue;
+            }
+            costs.insert((p, d), cost);
+
+            let forward = d.apply(p);
+            if self.is_open(forward) {
+                queue.push(Reverse((cost + MOVE_COST, forward, d)));
+            }
+            for turn in d.turns() {
+                queue.push(Reverse((cost + TURN_COST, p, turn)));
+            }
+        }
+
+        costs
+    }
+
+    /// Lowest score from the start, facing east, to the end facing any way,
+    /// and the costs from the start for every state.
+    fn best_score(&self) -> Option<(Cost, HashMap<State, Cost>)> {
+        let from_start = self.lowest_costs(&[(self.start, Direction::East)]);
+        let best = ALL_DIRECTIONS
+            .iter()
+            .filter_map(|&d| from_start.get(&(self.end, d)))
+            .min()
+            .copied()?;
+        Some((best, from_start))
+    }
+}
+
+const ALL_DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
 impl Sln {
     pub fn new() -> Sln {
         Sln {}
@@ -10,10 +135,123 @@ impl Sln {
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        match Maze::parse(&input).best_score() {
+            Some((best, _)) => best.to_string(),
+            None => template::unsolved("No path to the end"),
+        }
     }
 
     fn part_2(&self, input: String) -> String {
-        "".to_string()
+        let maze = Maze::parse(&input);
+        let Some((best, from_start)) = maze.best_score() else {
+            return template::unsolved("No path to the end");
+        };
+
+        // Walking a path backwards is walking forwards while facing the other
+        // way, so the cost from (p, d) to the end is the cost of reaching
+        // (p, opposite d) when starting at the end.
+        let end_states: Vec<State> = ALL_DIRECTIONS.iter().map(|&d| (maze.end, d)).collect();
+        let from_end = maze.lowest_costs(&end_states);
+
+        let mut tiles = HashSet::new();
+        for (&(p, d), &cost) in from_start.iter() {
+            if from_end
+                .get(&(p, d.opposite()))
+                .is_some_and(|&rest| cost + rest == best)
+            {
+                tiles.insert(p);
+            }
+        }
+
+        tiles.len().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############"#;
+        assert_eq!("7036", Sln::new().part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_1_second_example() {
+        let input = r#"#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################"#;
+        assert_eq!("11048", Sln::new().part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############"#;
+        assert_eq!("45", Sln::new().part_2(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_2_second_example() {
+        let input = r#"#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################"#;
+        assert_eq!("64", Sln::new().part_2(input.to_string()));
     }
 }
@@ -1,4 +1,9 @@
-use crate::utils::template::Solution;
+use itertools::Itertools;
+
+use crate::utils::parse;
+use crate::utils::sim::{Debugger, Runner};
+use crate::utils::template::{self, Solution};
+use crate::utils::vm::{Tracer, Vm, Word, B, C};
 
 pub struct Sln {}
 
@@ -6,14 +11,76 @@ impl Sln {
     pub fn new() -> Sln {
         Sln {}
     }
+
+    fn parse(input: &str) -> Vm {
+        let numbers = parse::ints::<Word>(input).unwrap();
+        let registers = [numbers[0], numbers[1], numbers[2]];
+        let program = numbers[3..].iter().map(|&n| n as u8).collect();
+        Vm::new(registers, program)
+    }
+
+    /// Lowest value of A that makes the program print itself. This relies on
+    /// the program being a loop that prints one number per iteration and
+    /// shifts A right by three bits, so the last numbers printed only depend
+    /// on the highest octal digits of A. Digits are found from the highest
+    /// down, keeping every candidate that reproduces the tail so far.
+    fn find_quine(vm: &Vm) -> Option<Word> {
+        let program = &vm.program;
+        let mut candidates: Vec<Word> = vec![0];
+        for i in (0..program.len()).rev() {
+            candidates = candidates
+                .iter()
+                .flat_map(|&c| (0..8).map(move |d| c * 8 + d))
+                .filter(|&a| {
+                    let mut attempt =
+                        Vm::new([a, vm.registers[B], vm.registers[C]], program.clone());
+                    attempt.run() == &program[i..]
+                })
+                .collect();
+        }
+
+        candidates.into_iter().filter(|&a| a > 0).min()
+    }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        Self::parse(&input).run().iter().join(",")
     }
 
     fn part_2(&self, input: String) -> String {
-        "".to_string()
+        match Self::find_quine(&Self::parse(&input)) {
+            Some(a) => a.to_string(),
+            None => template::unsolved("No value of A reproduces the program"),
+        }
+    }
+
+    fn step_through(&self, input: String) -> Option<Box<dyn Debugger>> {
+        Some(Box::new(Runner::new(Tracer::new(Self::parse(&input)))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0"#;
+        assert_eq!("4,6,3,5,6,3,5,2,1,0", Sln::new().part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0"#;
+        assert_eq!("117440", Sln::new().part_2(input.to_string()));
     }
 }
@@ -1,19 +1,222 @@
-use crate::utils::template::Solution;
+use std::collections::{HashMap, VecDeque};
 
-pub struct Sln {}
+use crate::utils::{
+    bitset::BitGrid,
+    dsu::DisjointSet,
+    parse,
+    template::{self, Solution},
+    Point,
+};
+
+/// Memory space of `size` by `size` cells, of which part 1 looks at the
+/// state after the first `bytes` bytes have fallen.
+pub struct Sln {
+    size: usize,
+    bytes: usize,
+}
 
 impl Sln {
     pub fn new() -> Sln {
-        Sln {}
+        Sln::with_size(71, 1024)
+    }
+
+    pub fn with_size(size: usize, bytes: usize) -> Sln {
+        Sln { size, bytes }
+    }
+
+    fn parse(input: &str) -> Vec<Point> {
+        parse::records::<(usize, usize)>(input, ",")
+            .unwrap()
+            .into_iter()
+            .map(|(x, y)| Point::new(x, y))
+            .collect()
+    }
+
+    fn neighbours(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        [p.left(), p.right(), p.up(), p.down()]
+            .into_iter()
+            .filter(|n| n.x < self.size && n.y < self.size)
+    }
+
+    /// Fewest steps from the top left to the bottom right corner, avoiding
+    /// corrupted cells.
+    fn shortest_path(&self, corrupted: &BitGrid) -> Option<usize> {
+        let start = Point::new(0, 0);
+        let exit = Point::new(self.size - 1, self.size - 1);
+        if corrupted.contains(start, ()) {
+            return None;
+        }
+
+        let mut seen = BitGrid::<()>::new(self.size, self.size);
+        let mut queue = VecDeque::from([(start, 0)]);
+        seen.insert(start, ());
+        while let Some((p, steps)) = queue.pop_front() {
+            if p == exit {
+                return Some(steps);
+            }
+            for n in self.neighbours(p) {
+                if !corrupted.contains(n, ()) && seen.insert(n, ()) {
+                    queue.push_back((n, steps + 1));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// First byte after which the exit can't be reached. Starts from the
+    /// grid with every byte fallen and removes them in reverse, joining each
+    /// freed cell to its free neighbours, until the corners are connected.
+    fn first_blocking_byte(&self, bytes: &[Point]) -> Option<Point> {
+        let index = |p: Point| p.y * self.size + p.x;
+        let mut corrupted = BitGrid::<()>::new(self.size, self.size);
+        for &b in bytes {
+            corrupted.insert(b, ());
+        }
+
+        let mut dsu = DisjointSet::new(self.size * self.size);
+        let free = |p: Point, corrupted: &BitGrid| !corrupted.contains(p, ());
+        let join = |p: Point, dsu: &mut DisjointSet, corrupted: &BitGrid| {
+            for n in self.neighbours(p) {
+                if free(n, corrupted) {
+                    dsu.union(index(p), index(n));
+                }
+            }
+        };
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let p = Point::new(x, y);
+                if free(p, &corrupted) {
+                    join(p, &mut dsu, &corrupted);
+                }
+            }
+        }
+
+        let (start, exit) = (0, self.size * self.size - 1);
+        if dsu.same(start, exit) {
+            return None;
+        }
+        // A cell can be hit more than once, and is only freed again when its
+        // first byte is removed
+        let mut first = HashMap::new();
+        for (i, &b) in bytes.iter().enumerate() {
+            first.entry(b).or_insert(i);
+        }
+        for (i, &b) in bytes.iter().enumerate().rev() {
+            if first[&b] != i {
+                continue;
+            }
+            corrupted.remove(b, ());
+            join(b, &mut dsu, &corrupted);
+            if dsu.same(start, exit) {
+                return Some(b);
+            }
+        }
+
+        None
     }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        let bytes = Self::parse(&input);
+        let mut corrupted = BitGrid::new(self.size, self.size);
+        for &b in bytes.iter().take(self.bytes) {
+            corrupted.insert(b, ());
+        }
+
+        match self.shortest_path(&corrupted) {
+            Some(steps) => steps.to_string(),
+            None => template::unsolved("The exit can't be reached"),
+        }
     }
 
     fn part_2(&self, input: String) -> String {
-        "".to_string()
+        match self.first_blocking_byte(&Self::parse(&input)) {
+            Some(p) => format!("{},{}", p.x, p.y),
+            None => template::unsolved("The exit is never cut off"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0"#;
+        assert_eq!("22", Sln::with_size(7, 12).part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0"#;
+        assert_eq!("6,1", Sln::with_size(7, 12).part_2(input.to_string()));
+    }
+
+    #[quickcheck]
+    fn test_first_blocking_byte_matches_bfs(bytes: Vec<(u8, u8)>) {
+        let sln = Sln::with_size(5, 0);
+        let bytes: Vec<Point> = bytes
+            .into_iter()
+            .map(|(x, y)| Point::new(x as usize % 5, y as usize % 5))
+            .collect();
+
+        let mut corrupted = BitGrid::new(5, 5);
+        let expected = bytes.iter().copied().find(|&b| {
+            corrupted.insert(b, ());
+            sln.shortest_path(&corrupted).is_none()
+        });
+        assert_eq!(expected, sln.first_blocking_byte(&bytes));
     }
 }
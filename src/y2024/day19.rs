@@ -1,19 +1,204 @@
-use crate::utils::template::Solution;
+use crate::utils::{parse, template::Solution};
 
 pub struct Sln {}
 
+type Count = u128;
+
+const COLOURS: [u8; 5] = *b"wubrg";
+
+fn colour_index(c: u8) -> usize {
+    COLOURS
+        .iter()
+        .position(|&k| k == c)
+        .unwrap_or_else(|| panic!("unknown stripe colour '{}'", c as char))
+}
+
+/// Prefix tree over the towel patterns. Node 0 is the root, which is never
+/// a child, so 0 also marks a missing child.
+struct Trie {
+    children: Vec<[usize; COLOURS.len()]>,
+    terminal: Vec<bool>,
+}
+
+impl Trie {
+    fn new() -> Trie {
+        Trie {
+            children: vec![[0; COLOURS.len()]],
+            terminal: vec![false],
+        }
+    }
+
+    fn insert(&mut self, pattern: &str) {
+        let mut node = 0;
+        for c in pattern.bytes() {
+            let i = colour_index(c);
+            if self.children[node][i] == 0 {
+                self.children.push([0; COLOURS.len()]);
+                self.terminal.push(false);
+                self.children[node][i] = self.children.len() - 1;
+            }
+            node = self.children[node][i];
+        }
+        self.terminal[node] = true;
+    }
+
+    /// Lengths of the patterns that `s` starts with, shortest first.
+    fn prefix_lengths<'a>(&'a self, s: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        s.iter()
+            .scan(0, |node, &c| {
+                *node = self.children[*node][colour_index(c)];
+                (*node != 0).then_some(*node)
+            })
+            .enumerate()
+            .filter(|&(_, node)| self.terminal[node])
+            .map(|(i, _)| i + 1)
+    }
+
+    /// Number of ways to build `design` by lining up patterns. `ways[i]`
+    /// counts the arrangements of the suffix starting at `i`.
+    fn arrangements(&self, design: &str) -> Count {
+        let design = design.as_bytes();
+        let mut ways: Vec<Count> = vec![0; design.len() + 1];
+        ways[design.len()] = 1;
+        for i in (0..design.len()).rev() {
+            ways[i] = self
+                .prefix_lengths(&design[i..])
+                .map(|len| ways[i + len])
+                .sum();
+        }
+
+        ways[0]
+    }
+}
+
 impl Sln {
     pub fn new() -> Sln {
         Sln {}
     }
+
+    fn parse(input: &str) -> (Trie, Vec<&str>) {
+        let sections = parse::sections(input);
+        let mut trie = Trie::new();
+        for pattern in sections[0].split(',') {
+            trie.insert(pattern.trim());
+        }
+        let designs = sections[1]
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        (trie, designs)
+    }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        let (trie, designs) = Self::parse(&input);
+        designs
+            .iter()
+            .filter(|d| trie.arrangements(d) > 0)
+            .count()
+            .to_string()
     }
 
     fn part_2(&self, input: String) -> String {
-        "".to_string()
+        let (trie, designs) = Self::parse(&input);
+        designs
+            .iter()
+            .map(|d| trie.arrangements(d))
+            .sum::<Count>()
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb"#;
+        assert_eq!("6", Sln::new().part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb"#;
+        assert_eq!("16", Sln::new().part_2(input.to_string()));
+    }
+
+    #[test]
+    fn test_long_design_does_not_overflow() {
+        let mut trie = Trie::new();
+        trie.insert("w");
+        trie.insert("ww");
+        // Fibonacci number F(121), which doesn't fit in a u64
+        let design = "w".repeat(120);
+        assert_eq!(8670007398507948658051921, trie.arrangements(&design));
+    }
+
+    #[derive(Clone, Debug)]
+    struct Towels {
+        patterns: Vec<String>,
+        design: String,
+    }
+
+    impl Arbitrary for Towels {
+        fn arbitrary(g: &mut Gen) -> Towels {
+            // Two colours make overlapping patterns likely
+            let mut stripes = |len: usize| -> String {
+                (0..len).map(|_| *g.choose(&['w', 'u']).unwrap()).collect()
+            };
+            let patterns = (0..4).map(|i| stripes(1 + i % 3)).collect();
+            let design = stripes(12);
+            Towels { patterns, design }
+        }
+    }
+
+    fn brute_force(patterns: &[String], design: &str) -> Count {
+        if design.is_empty() {
+            return 1;
+        }
+        patterns
+            .iter()
+            .filter(|p| design.starts_with(p.as_str()))
+            .map(|p| brute_force(patterns, &design[p.len()..]))
+            .sum()
+    }
+
+    #[quickcheck]
+    fn test_arrangements_match_brute_force(towels: Towels) {
+        let mut patterns = towels.patterns;
+        patterns.sort();
+        patterns.dedup();
+        let mut trie = Trie::new();
+        for p in &patterns {
+            trie.insert(p);
+        }
+        assert_eq!(
+            brute_force(&patterns, &towels.design),
+            trie.arrangements(&towels.design)
+        );
     }
 }
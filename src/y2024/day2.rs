@@ -1,19 +1,232 @@
-use crate::utils::template::Solution;
+use std::io;
+
+use itertools::Itertools;
+
+use crate::utils::{
+    parse,
+    template::Solution,
+    viz::{Canvas, Visualizer},
+};
 
 pub struct Sln {}
 
+type IntType = i32;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Safety {
+    Safe,
+    /// Safe once the level at this index is removed.
+    SafeWithout(usize),
+    Unsafe,
+}
+
 impl Sln {
     pub fn new() -> Sln {
         Sln {}
     }
+
+    fn parse(input: &str) -> Vec<Vec<IntType>> {
+        input
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| parse::ints(line).unwrap())
+            .collect()
+    }
+
+    /// Index of the earlier level in the first pair of neighbouring levels
+    /// that doesn't change by 1 to 3 in the given direction, ignoring the
+    /// level at `skip`.
+    fn first_unsafe_step(
+        levels: &[IntType],
+        skip: Option<usize>,
+        increasing: bool,
+    ) -> Option<usize> {
+        let mut prev: Option<usize> = None;
+        for i in (0..levels.len()).filter(|&i| Some(i) != skip) {
+            if let Some(p) = prev {
+                let diff = if increasing {
+                    levels[i] - levels[p]
+                } else {
+                    levels[p] - levels[i]
+                };
+                if !(1..=3).contains(&diff) {
+                    return Some(p);
+                }
+            }
+            prev = Some(i);
+        }
+
+        None
+    }
+
+    /// Whether the report is safe, and if not which level the Problem
+    /// Dampener should remove. A removed level must be part of the first
+    /// unsafe pair, as every other pair stays neighbours, so only two
+    /// removals per direction need checking.
+    fn analyze(levels: &[IntType]) -> Safety {
+        let directions = [true, false];
+        if directions
+            .iter()
+            .any(|&inc| Self::first_unsafe_step(levels, None, inc).is_none())
+        {
+            return Safety::Safe;
+        }
+
+        directions
+            .iter()
+            .flat_map(|&inc| {
+                let i = Self::first_unsafe_step(levels, None, inc).unwrap();
+                [i, i + 1]
+                    .into_iter()
+                    .filter(move |&k| Self::first_unsafe_step(levels, Some(k), inc).is_none())
+            })
+            .min()
+            .map_or(Safety::Unsafe, Safety::SafeWithout)
+    }
+
+    /// Reports that are only safe thanks to the Problem Dampener, as the
+    /// report's index and the index of the level it removes.
+    fn dampened(reports: &[Vec<IntType>]) -> Vec<(usize, usize)> {
+        reports
+            .iter()
+            .enumerate()
+            .filter_map(|(i, levels)| match Self::analyze(levels) {
+                Safety::SafeWithout(k) => Some((i, k)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The report with the level at index `k` in brackets.
+    fn mark_level(levels: &[IntType], k: usize) -> String {
+        levels
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                if i == k {
+                    format!("[{}]", l)
+                } else {
+                    l.to_string()
+                }
+            })
+            .join(" ")
+    }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        Self::parse(&input)
+            .iter()
+            .filter(|levels| Self::analyze(levels) == Safety::Safe)
+            .count()
+            .to_string()
     }
 
     fn part_2(&self, input: String) -> String {
-        "".to_string()
+        Self::parse(&input)
+            .iter()
+            .filter(|levels| Self::analyze(levels) != Safety::Unsafe)
+            .count()
+            .to_string()
+    }
+
+    /// Lists the reports the Problem Dampener makes safe, with the removed
+    /// level in brackets.
+    fn visualize(&self, input: String, viz: &mut Visualizer) -> io::Result<bool> {
+        let reports = Self::parse(&input);
+        let dampened = Self::dampened(&reports);
+        let mut lines = vec![format!(
+            "{} reports are safe with the Problem Dampener",
+            dampened.len()
+        )];
+        lines.extend(
+            dampened
+                .iter()
+                .map(|&(i, k)| format!("{:>4}: {}", i + 1, Self::mark_level(&reports[i], k))),
+        );
+        let grid: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+        viz.frame(&Canvas::from_chars(&grid))?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"7 6 4 2 1
+1 2 7 8 9
+9 7 6 2 1
+1 3 2 4 5
+8 6 4 4 1
+1 3 6 7 9"#;
+        assert_eq!("2", Sln::new().part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"7 6 4 2 1
+1 2 7 8 9
+9 7 6 2 1
+1 3 2 4 5
+8 6 4 4 1
+1 3 6 7 9"#;
+        assert_eq!("4", Sln::new().part_2(input.to_string()));
+    }
+
+    #[test]
+    fn test_analyze() {
+        assert_eq!(Safety::Safe, Sln::analyze(&[7, 6, 4, 2, 1]));
+        assert_eq!(Safety::Unsafe, Sln::analyze(&[1, 2, 7, 8, 9]));
+        assert_eq!(Safety::SafeWithout(1), Sln::analyze(&[1, 3, 2, 4, 5]));
+        assert_eq!(Safety::SafeWithout(2), Sln::analyze(&[8, 6, 4, 4, 1]));
+        assert_eq!(Safety::SafeWithout(0), Sln::analyze(&[9, 1, 2, 3]));
+        assert_eq!(Safety::SafeWithout(3), Sln::analyze(&[1, 2, 3, 9]));
+    }
+
+    #[test]
+    fn test_dampened() {
+        let input = r#"7 6 4 2 1
+1 2 7 8 9
+9 7 6 2 1
+1 3 2 4 5
+8 6 4 4 1
+1 3 6 7 9"#;
+        // "Safe by removing the second level" and "Safe by removing the
+        // third level"
+        assert_eq!(vec![(3, 1), (4, 2)], Sln::dampened(&Sln::parse(input)));
+    }
+
+    #[test]
+    fn test_mark_level() {
+        assert_eq!("1 [3] 2 4 5", Sln::mark_level(&[1, 3, 2, 4, 5], 1));
+        assert_eq!("8 6 [4] 4 1", Sln::mark_level(&[8, 6, 4, 4, 1], 2));
+    }
+
+    #[quickcheck]
+    fn test_analyze_matches_every_removal(levels: Vec<i8>) {
+        let levels: Vec<IntType> = levels.into_iter().map(|l| (l % 8) as IntType).collect();
+        let safe = |levels: &[IntType]| {
+            levels.windows(2).all(|w| (1..=3).contains(&(w[1] - w[0])))
+                || levels.windows(2).all(|w| (1..=3).contains(&(w[0] - w[1])))
+        };
+        let without = |k: usize| {
+            let mut v = levels.clone();
+            v.remove(k);
+            v
+        };
+
+        let expected = if safe(&levels) {
+            Safety::Safe
+        } else {
+            (0..levels.len())
+                .find(|&k| safe(&without(k)))
+                .map_or(Safety::Unsafe, Safety::SafeWithout)
+        };
+        assert_eq!(expected, Sln::analyze(&levels));
     }
 }
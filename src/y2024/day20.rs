@@ -1,19 +1,224 @@
-use crate::utils::template::Solution;
+use std::io;
 
-pub struct Sln {}
+use crate::utils::{
+    parse,
+    template::Solution,
+    viz::{Canvas, Visualizer},
+    Counter, Point,
+};
+
+/// Counts cheats that save at least `threshold` picoseconds.
+pub struct Sln {
+    threshold: usize,
+}
+
+/// The single track from start to end, with each cell's position along it.
+/// The distance from the start is the position, and the distance to the
+/// end is the track length minus it.
+struct Track {
+    path: Vec<Point>,
+    index: Vec<Vec<Option<usize>>>,
+}
+
+impl Track {
+    fn parse(input: &str) -> Track {
+        let grid = parse::char_grid(input);
+        let find = |target: char| {
+            grid.iter()
+                .enumerate()
+                .find_map(|(y, row)| {
+                    row.iter()
+                        .position(|&c| c == target)
+                        .map(|x| Point::new(x, y))
+                })
+                .unwrap()
+        };
+        let (start, end) = (find('S'), find('E'));
+        let open = |p: Point| {
+            grid.get(p.y)
+                .and_then(|row| row.get(p.x))
+                .is_some_and(|&c| c != '#')
+        };
+
+        let mut index: Vec<Vec<Option<usize>>> =
+            grid.iter().map(|row| vec![None; row.len()]).collect();
+        let mut path = vec![start];
+        index[start.y][start.x] = Some(0);
+        let mut p = start;
+        while p != end {
+            p = [p.left(), p.right(), p.up(), p.down()]
+                .into_iter()
+                .find(|&n| open(n) && index[n.y][n.x].is_none())
+                .expect("the track has no branches or dead ends");
+            index[p.y][p.x] = Some(path.len());
+            path.push(p);
+        }
+
+        Track { path, index }
+    }
+
+    fn position(&self, p: Point) -> Option<usize> {
+        *self.index.get(p.y)?.get(p.x)?
+    }
+
+    /// Time saved by every cheat of at most `max_len` picoseconds that saves
+    /// any time. A cheat goes straight to any track cell within that
+    /// Manhattan distance, walls or not, so each cell's reachable cells are
+    /// enumerated directly rather than searched for.
+    fn savings(&self, max_len: usize) -> Counter<usize> {
+        let max = max_len as isize;
+        let mut savings = Counter::new();
+        for (i, &p) in self.path.iter().enumerate() {
+            for dy in -max..=max {
+                let width = max - dy.abs();
+                for dx in -width..=width {
+                    let (x, y) = (p.x as isize + dx, p.y as isize + dy);
+                    if x < 0 || y < 0 {
+                        continue;
+                    }
+                    let len = dx.unsigned_abs() + dy.unsigned_abs();
+                    if let Some(j) = self.position(Point::new(x as usize, y as usize)) {
+                        if j > i + len {
+                            savings.add(j - i - len);
+                        }
+                    }
+                }
+            }
+        }
+
+        savings
+    }
+}
+
+/// One line per amount saved, smallest first, e.g. `  64: 1 #`.
+fn histogram(savings: &Counter<usize>) -> Vec<String> {
+    let mut rows: Vec<(usize, usize)> = savings.iter().map(|(&s, n)| (s, n)).collect();
+    rows.sort();
+    let widest = rows.iter().map(|&(_, n)| n).max().unwrap_or(1);
+    rows.iter()
+        .map(|&(saved, n)| {
+            // Scale bars to at most 60 characters, but always show one
+            let bar = "#".repeat((n * 60).div_ceil(widest));
+            format!("{:>5}: {} {}", saved, n, bar)
+        })
+        .collect()
+}
 
 impl Sln {
     pub fn new() -> Sln {
-        Sln {}
+        Sln::with_threshold(100)
+    }
+
+    pub fn with_threshold(threshold: usize) -> Sln {
+        Sln { threshold }
+    }
+
+    fn count_cheats(&self, input: &str, max_len: usize) -> usize {
+        Track::parse(input)
+            .savings(max_len)
+            .iter()
+            .filter(|&(&saved, _)| saved >= self.threshold)
+            .map(|(_, n)| n)
+            .sum()
     }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        self.count_cheats(&input, 2).to_string()
     }
 
     fn part_2(&self, input: String) -> String {
-        "".to_string()
+        self.count_cheats(&input, 20).to_string()
+    }
+
+    fn visualize(&self, input: String, viz: &mut Visualizer) -> io::Result<bool> {
+        let track = Track::parse(&input);
+        for max_len in [2, 20] {
+            let mut lines = vec![format!("cheats of up to {} picoseconds", max_len)];
+            lines.extend(histogram(&track.savings(max_len)));
+            let grid: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+            viz.frame(&Canvas::from_chars(&grid))?;
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############"#;
+        assert_eq!("5", Sln::with_threshold(20).part_1(input.to_string()));
+        assert_eq!("44", Sln::with_threshold(1).part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############"#;
+        assert_eq!("285", Sln::with_threshold(50).part_2(input.to_string()));
+        assert_eq!("3", Sln::with_threshold(76).part_2(input.to_string()));
+    }
+
+    #[test]
+    fn test_histogram() {
+        let input = r#"###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############"#;
+        let savings = Track::parse(input).savings(2);
+        let counts: Vec<String> = histogram(&savings)
+            .iter()
+            .map(|line| line.rsplit_once(' ').unwrap().0.trim().to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                "2: 14", "4: 14", "6: 2", "8: 4", "10: 2", "12: 3", "20: 1", "36: 1", "38: 1",
+                "40: 1", "64: 1"
+            ],
+            counts
+        );
     }
 }
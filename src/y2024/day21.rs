@@ -1,19 +1,297 @@
-use crate::utils::template::Solution;
+use std::{collections::HashMap, io};
+
+use crate::utils::{
+    memo::Memo,
+    template::Solution,
+    viz::{Canvas, Visualizer},
+};
 
 pub struct Sln {}
 
+type Cost = u64;
+
+/// Key rows from top to bottom. A space marks the gap the arm must never be
+/// over.
+const NUMERIC: [&str; 4] = ["789", "456", "123", " 0A"];
+const DIRECTIONAL: [&str; 2] = [" ^A", "<v>"];
+
+struct Keypad {
+    /// (row, column) of each key.
+    keys: HashMap<char, (usize, usize)>,
+    gap: (usize, usize),
+}
+
+impl Keypad {
+    fn new(layout: &[&str]) -> Keypad {
+        let mut keys = HashMap::new();
+        let mut gap = None;
+        for (r, row) in layout.iter().enumerate() {
+            for (c, key) in row.chars().enumerate() {
+                match key {
+                    ' ' => gap = Some((r, c)),
+                    _ => {
+                        keys.insert(key, (r, c));
+                    }
+                }
+            }
+        }
+
+        Keypad {
+            keys,
+            gap: gap.expect("keypad has a gap"),
+        }
+    }
+
+    /// Presses on a directional keypad that move the arm from `from` to `to`
+    /// and push it. Only the two paths that make all horizontal moves in one
+    /// go need considering, as any zigzag costs more presses further up, and
+    /// a path is dropped if it would cross the gap.
+    fn moves(&self, from: char, to: char) -> Vec<String> {
+        let (r1, c1) = self.keys[&from];
+        let (r2, c2) = self.keys[&to];
+        let vertical = if r2 > r1 { "v" } else { "^" }.repeat(r1.abs_diff(r2));
+        let horizontal = if c2 > c1 { ">" } else { "<" }.repeat(c1.abs_diff(c2));
+
+        let mut moves = vec![];
+        if (r1, c2) != self.gap {
+            moves.push(format!("{}{}A", horizontal, vertical));
+        }
+        if (r2, c1) != self.gap {
+            moves.push(format!("{}{}A", vertical, horizontal));
+        }
+        moves.dedup();
+        moves
+    }
+}
+
+/// A chain of robots on directional keypads, with a person pressing the
+/// keypad of the last one. Costs are the presses the person makes.
+struct Robots {
+    numeric: Keypad,
+    directional: Keypad,
+    memo: Memo<(char, char, usize), Cost>,
+}
+
+impl Robots {
+    fn new() -> Robots {
+        Robots {
+            numeric: Keypad::new(&NUMERIC),
+            directional: Keypad::new(&DIRECTIONAL),
+            memo: Memo::new(),
+        }
+    }
+
+    /// Presses needed for `depth` robots to type `sequence` on a directional
+    /// keypad, each starting on `A`. At depth 0 the person types it directly.
+    fn sequence_cost(&mut self, sequence: &str, depth: usize) -> Cost {
+        if depth == 0 {
+            return sequence.len() as Cost;
+        }
+
+        let mut cost = 0;
+        let mut from = 'A';
+        for to in sequence.chars() {
+            cost += self.move_cost(from, to, depth);
+            from = to;
+        }
+        cost
+    }
+
+    /// Presses needed for the robot at `depth` to move from `from` to `to`
+    /// and push it. Every robot below it is back on `A` after each push, so
+    /// the cost only depends on these three values.
+    fn move_cost(&mut self, from: char, to: char, depth: usize) -> Cost {
+        if let Some(&cost) = self.memo.get(&(from, to, depth)) {
+            return cost;
+        }
+
+        let cost = self
+            .directional
+            .moves(from, to)
+            .iter()
+            .map(|m| self.sequence_cost(m, depth - 1))
+            .min()
+            .unwrap();
+        self.memo.insert((from, to, depth), cost);
+        cost
+    }
+
+    /// The cheapest of `moves` at `depth`, and its cost.
+    fn cheapest(&mut self, moves: Vec<String>, depth: usize) -> (String, Cost) {
+        moves
+            .into_iter()
+            .map(|m| {
+                let cost = self.sequence_cost(&m, depth);
+                (m, cost)
+            })
+            .min_by_key(|(_, cost)| *cost)
+            .unwrap()
+    }
+
+    /// Fewest presses to type `code` on the numeric keypad through `robots`
+    /// robots on directional keypads.
+    fn code_cost(&mut self, code: &str, robots: usize) -> Cost {
+        let mut cost = 0;
+        let mut from = 'A';
+        for to in code.chars() {
+            let moves = self.numeric.moves(from, to);
+            cost += self.cheapest(moves, robots).1;
+            from = to;
+        }
+        cost
+    }
+
+    /// One of the shortest sequences the person can press to type `code`.
+    fn shortest_sequence(&mut self, code: &str, robots: usize) -> String {
+        let mut sequence = String::new();
+        let mut from = 'A';
+        for to in code.chars() {
+            let moves = self.numeric.moves(from, to);
+            let (best, _) = self.cheapest(moves, robots);
+            sequence += &self.expand(&best, robots);
+            from = to;
+        }
+        sequence
+    }
+
+    /// Turns a sequence for the robot at `depth` into the presses that make
+    /// it, always taking the cheapest moves.
+    fn expand(&mut self, sequence: &str, depth: usize) -> String {
+        if depth == 0 {
+            return sequence.to_string();
+        }
+
+        let mut expanded = String::new();
+        let mut from = 'A';
+        for to in sequence.chars() {
+            let moves = self.directional.moves(from, to);
+            let (best, _) = self.cheapest(moves, depth - 1);
+            expanded += &self.expand(&best, depth - 1);
+            from = to;
+        }
+        expanded
+    }
+}
+
 impl Sln {
     pub fn new() -> Sln {
         Sln {}
     }
+
+    /// Sum over the codes of their cheapest press count times their numeric
+    /// part, with `robots` robots on directional keypads.
+    fn total_complexity(input: &str, robots: usize) -> Cost {
+        let mut chain = Robots::new();
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|code| {
+                let number: Cost = code.trim_end_matches('A').parse().unwrap();
+                chain.code_cost(code, robots) * number
+            })
+            .sum()
+    }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        Self::total_complexity(&input, 2).to_string()
     }
 
     fn part_2(&self, input: String) -> String {
-        "".to_string()
+        Self::total_complexity(&input, 25).to_string()
+    }
+
+    /// Shows a shortest sequence for each code with part 1's two robots.
+    fn visualize(&self, input: String, viz: &mut Visualizer) -> io::Result<bool> {
+        let mut chain = Robots::new();
+        let grid: Vec<Vec<char>> = input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|code| {
+                let sequence = chain.shortest_sequence(code, 2);
+                format!("{}: {} ({})", code, sequence, sequence.len())
+                    .chars()
+                    .collect()
+            })
+            .collect();
+        viz.frame(&Canvas::from_chars(&grid))?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"029A
+980A
+179A
+456A
+379A"#;
+        assert_eq!("126384", Sln::new().part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"029A
+980A
+179A
+456A
+379A"#;
+        assert_eq!("154115708116294", Sln::new().part_2(input.to_string()));
+    }
+
+    #[test]
+    fn test_moves_avoid_gap() {
+        let numeric = Keypad::new(&NUMERIC);
+        assert_eq!(vec!["^<<A"], numeric.moves('A', '1'));
+        assert_eq!(vec![">>vA"], numeric.moves('1', 'A'));
+        assert_eq!(vec!["<^^A", "^^<A"], numeric.moves('A', '5'));
+        let directional = Keypad::new(&DIRECTIONAL);
+        assert_eq!(vec!["v<<A"], directional.moves('A', '<'));
+        assert_eq!(vec!["A"], directional.moves('v', 'v'));
+    }
+
+    /// Keys typed on `keypad` by pressing `presses` on the keypad that
+    /// controls it, checking the arm never points at the gap.
+    fn press(keypad: &Keypad, presses: &str) -> String {
+        let (mut r, mut c) = keypad.keys[&'A'];
+        let mut typed = String::new();
+        for p in presses.chars() {
+            match p {
+                '^' => r -= 1,
+                'v' => r += 1,
+                '<' => c -= 1,
+                '>' => c += 1,
+                'A' => typed.push(*keypad.keys.iter().find(|(_, &k)| k == (r, c)).unwrap().0),
+                _ => panic!("unexpected press {}", p),
+            }
+            assert_ne!(keypad.gap, (r, c));
+        }
+        typed
+    }
+
+    #[test]
+    fn test_shortest_sequence() {
+        for (code, robots) in [("029A", 2), ("379A", 2), ("456A", 0), ("980A", 4)] {
+            let mut chain = Robots::new();
+            let sequence = chain.shortest_sequence(code, robots);
+            assert_eq!(chain.code_cost(code, robots), sequence.len() as Cost);
+
+            let mut typed = sequence;
+            for _ in 0..robots {
+                typed = press(&chain.directional, &typed);
+            }
+            assert_eq!(code, press(&chain.numeric, &typed));
+        }
+
+        let mut chain = Robots::new();
+        assert_eq!(68, chain.shortest_sequence("029A", 2).len());
     }
 }
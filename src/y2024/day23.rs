@@ -1,4 +1,8 @@
-use crate::utils::template::Solution;
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::utils::{graph::Graph, template::Solution};
 
 pub struct Sln {}
 
@@ -6,14 +10,125 @@ impl Sln {
     pub fn new() -> Sln {
         Sln {}
     }
+
+    /// The network, with computers numbered in alphabetical order of their
+    /// names.
+    fn parse(input: &str) -> (Graph, Vec<&str>) {
+        let links: Vec<(&str, &str)> = input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.split_once('-').unwrap())
+            .collect();
+        let names: Vec<&str> = links
+            .iter()
+            .flat_map(|&(a, b)| [a, b])
+            .sorted()
+            .dedup()
+            .collect();
+        let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+
+        let mut graph = Graph::new(names.len());
+        for (a, b) in links {
+            graph.add_edge(index[a], index[b]);
+        }
+
+        (graph, names)
+    }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        let (graph, names) = Self::parse(&input);
+        graph
+            .triangles()
+            .filter(|&(a, b, c)| [a, b, c].iter().any(|&i| names[i].starts_with('t')))
+            .count()
+            .to_string()
     }
 
     fn part_2(&self, input: String) -> String {
-        "".to_string()
+        let (graph, names) = Self::parse(&input);
+        // Nodes are numbered alphabetically, so the clique comes out sorted
+        graph.max_clique().iter().map(|&i| names[i]).join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn"#;
+        assert_eq!("7", Sln::new().part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = r#"kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn"#;
+        assert_eq!("co,de,ka,ta", Sln::new().part_2(input.to_string()));
     }
 }
@@ -1,7 +1,312 @@
-use crate::utils::template::Solution;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    io,
+};
+
+use itertools::Itertools;
+
+use crate::utils::{
+    parse,
+    template::{self, Solution},
+    toposort::toposort,
+    viz::Visualizer,
+};
 
 pub struct Sln {}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+impl Op {
+    fn parse(s: &str) -> Op {
+        match s {
+            "AND" => Op::And,
+            "OR" => Op::Or,
+            "XOR" => Op::Xor,
+            _ => panic!("unknown gate '{}'", s),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Xor => "XOR",
+        }
+    }
+
+    fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            Op::And => a && b,
+            Op::Or => a || b,
+            Op::Xor => a ^ b,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Gate {
+    a: String,
+    op: Op,
+    b: String,
+    out: String,
+}
+
+impl Gate {
+    fn has_input(&self, wire: &str) -> bool {
+        self.a == wire || self.b == wire
+    }
+
+    /// Whether both inputs are bits of the numbers being added.
+    fn reads_inputs(&self) -> bool {
+        [&self.a, &self.b]
+            .iter()
+            .all(|w| w.starts_with('x') || w.starts_with('y'))
+    }
+
+    fn reads_first_bits(&self) -> bool {
+        self.reads_inputs() && self.a[1..] == *"00" && self.b[1..] == *"00"
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Circuit {
+    initial: HashMap<String, bool>,
+    gates: Vec<Gate>,
+}
+
+impl Circuit {
+    fn parse(input: &str) -> Circuit {
+        let sections = parse::sections(input);
+        let initial = sections[0]
+            .lines()
+            .filter_map(|line| line.split_once(": "))
+            .map(|(wire, value)| (wire.to_string(), value.trim() == "1"))
+            .collect();
+        let gates = sections[1]
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (a, op, b, _, out) = line.split_whitespace().collect_tuple().unwrap();
+                Gate {
+                    a: a.to_string(),
+                    op: Op::parse(op),
+                    b: b.to_string(),
+                    out: out.to_string(),
+                }
+            })
+            .collect();
+
+        Circuit { initial, gates }
+    }
+
+    /// Gate indices with every gate after the gates feeding it, or `None` if
+    /// the wiring loops.
+    fn evaluation_order(&self) -> Option<Vec<usize>> {
+        let mut readers: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, gate) in self.gates.iter().enumerate() {
+            readers.entry(&gate.a).or_default().push(i);
+            readers.entry(&gate.b).or_default().push(i);
+        }
+        let nodes: Vec<usize> = (0..self.gates.len()).collect();
+        toposort(&nodes, |&i| {
+            readers
+                .get(self.gates[i].out.as_str())
+                .cloned()
+                .unwrap_or_default()
+        })
+        .ok()
+    }
+
+    /// Value of every wire, given the input wires' values.
+    fn evaluate(&self, order: &[usize], inputs: &HashMap<String, bool>) -> HashMap<String, bool> {
+        let mut values = inputs.clone();
+        for &i in order {
+            let gate = &self.gates[i];
+            let a = values.get(&gate.a).copied().unwrap_or(false);
+            let b = values.get(&gate.b).copied().unwrap_or(false);
+            values.insert(gate.out.clone(), gate.op.apply(a, b));
+        }
+        values
+    }
+
+    /// The number formed by the wires starting with `prefix`, lowest bit in
+    /// the wire numbered 00.
+    fn number(values: &HashMap<String, bool>, prefix: char) -> u64 {
+        values
+            .iter()
+            .filter(|(wire, &on)| on && wire.starts_with(prefix))
+            .map(|(wire, _)| 1 << wire[1..].parse::<u32>().unwrap())
+            .sum()
+    }
+
+    /// Number of bits in each number being added.
+    fn input_bits(&self) -> usize {
+        self.gates
+            .iter()
+            .flat_map(|g| [&g.a, &g.b])
+            .filter(|w| w.starts_with('x'))
+            .unique()
+            .count()
+    }
+
+    fn highest_output(&self) -> String {
+        format!("z{:02}", self.input_bits())
+    }
+
+    /// Output wires of gates that don't fit the shape of a ripple-carry
+    /// adder. Bit `i` of the sum should be `(x XOR y) XOR carry`, and the
+    /// carry out `(x AND y) OR ((x XOR y) AND carry)`, apart from bit 0,
+    /// which has no carry in, and the top output, which is the last carry.
+    fn misplaced_wires(&self) -> Vec<String> {
+        let highest = self.highest_output();
+        let feeds = |wire: &str, op: Op| self.gates.iter().any(|g| g.op == op && g.has_input(wire));
+
+        let mut wrong = HashSet::new();
+        for gate in &self.gates {
+            let is_output = gate.out.starts_with('z');
+            let misplaced = match gate.op {
+                _ if gate.out == highest => gate.op != Op::Or,
+                Op::Xor if gate.reads_first_bits() => gate.out != "z00",
+                // Sum of the input bits, which goes on to the output XOR
+                Op::Xor if gate.reads_inputs() => is_output || !feeds(&gate.out, Op::Xor),
+                // Output XOR of the sum with the carry in
+                Op::Xor => !is_output,
+                // Carry out of bit 0
+                Op::And if gate.reads_first_bits() => is_output,
+                // Both halves of the carry go into an OR
+                Op::And => is_output || !feeds(&gate.out, Op::Or),
+                Op::Or => is_output,
+            };
+            if misplaced {
+                wrong.insert(gate.out.clone());
+            }
+        }
+
+        wrong.into_iter().sorted().collect()
+    }
+
+    fn swap_outputs(&mut self, a: &str, b: &str) {
+        for gate in self.gates.iter_mut() {
+            if gate.out == a {
+                gate.out = b.to_string();
+            } else if gate.out == b {
+                gate.out = a.to_string();
+            }
+        }
+    }
+
+    /// Whether the circuit adds correctly, checked on single bits, carries
+    /// rippling through every position and a few mixed numbers.
+    fn adds_correctly(&self) -> bool {
+        let Some(order) = self.evaluation_order() else {
+            return false;
+        };
+        let bits = self.input_bits();
+        let mask = (1u64 << bits) - 1;
+        let mut cases = vec![
+            (mask, 1),
+            (mask, mask),
+            (0x5555_5555_5555 & mask, 0x3333_3333_3333 & mask),
+        ];
+        for i in 0..bits {
+            cases.push((1 << i, 0));
+            cases.push((0, 1 << i));
+            cases.push((1 << i, 1 << i));
+        }
+
+        cases.into_iter().all(|(x, y)| {
+            let mut inputs = HashMap::new();
+            for i in 0..bits {
+                inputs.insert(format!("x{:02}", i), x >> i & 1 == 1);
+                inputs.insert(format!("y{:02}", i), y >> i & 1 == 1);
+            }
+            Self::number(&self.evaluate(&order, &inputs), 'z') == x + y
+        })
+    }
+
+    /// The pairs of gates whose outputs were swapped. The misplaced wires
+    /// are paired up every possible way until swapping them back gives a
+    /// working adder.
+    fn swapped_pairs(&self) -> Option<Vec<(String, String)>> {
+        fn pairings(wires: &[String]) -> Vec<Vec<(String, String)>> {
+            let Some((first, rest)) = wires.split_first() else {
+                return vec![vec![]];
+            };
+            let mut result = vec![];
+            for i in 0..rest.len() {
+                let mut others = rest.to_vec();
+                let partner = others.remove(i);
+                for mut pairing in pairings(&others) {
+                    pairing.push((first.clone(), partner.clone()));
+                    result.push(pairing);
+                }
+            }
+            result
+        }
+
+        let wires = self.misplaced_wires();
+        if !wires.len().is_multiple_of(2) {
+            return None;
+        }
+        pairings(&wires).into_iter().find(|pairing| {
+            let mut repaired = self.clone();
+            for (a, b) in pairing {
+                repaired.swap_outputs(a, b);
+            }
+            repaired.adds_correctly()
+        })
+    }
+
+    /// The circuit in Graphviz DOT format, with one node per gate and per
+    /// input or output wire.
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph circuit {\n    rankdir=LR;\n");
+        for (i, gate) in self.gates.iter().enumerate() {
+            let color = match gate.op {
+                Op::And => "lightblue",
+                Op::Or => "lightgreen",
+                Op::Xor => "lightpink",
+            };
+            writeln!(
+                dot,
+                "    g{} [label=\"{}\\n{}\", shape=box, style=filled, fillcolor={}];",
+                i,
+                gate.op.name(),
+                gate.out,
+                color
+            )
+            .unwrap();
+        }
+
+        let producer: HashMap<&str, usize> = self
+            .gates
+            .iter()
+            .enumerate()
+            .map(|(i, g)| (g.out.as_str(), i))
+            .collect();
+        let node = |wire: &str| match producer.get(wire) {
+            Some(i) => format!("g{}", i),
+            None => wire.to_string(),
+        };
+        for (i, gate) in self.gates.iter().enumerate() {
+            for input in [&gate.a, &gate.b] {
+                writeln!(dot, "    {} -> g{};", node(input), i).unwrap();
+            }
+            if gate.out.starts_with('z') {
+                writeln!(dot, "    g{} -> {};", i, gate.out).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
 impl Sln {
     pub fn new() -> Sln {
         Sln {}
@@ -10,10 +315,181 @@ impl Sln {
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        let circuit = Circuit::parse(&input);
+        match circuit.evaluation_order() {
+            Some(order) => {
+                Circuit::number(&circuit.evaluate(&order, &circuit.initial), 'z').to_string()
+            }
+            None => template::unsolved("The circuit has a loop"),
+        }
     }
 
     fn part_2(&self, input: String) -> String {
-        "".to_string()
+        match Circuit::parse(&input).swapped_pairs() {
+            Some(pairs) => pairs.iter().flat_map(|(a, b)| [a, b]).sorted().join(","),
+            None => template::unsolved("Couldn't repair the adder"),
+        }
+    }
+
+    /// Saves the circuit as `circuit.dot` in the frames directory, for
+    /// rendering with `dot -Tsvg`.
+    fn visualize(&self, input: String, viz: &mut Visualizer) -> io::Result<bool> {
+        let path = viz.save("circuit.dot", &Circuit::parse(&input).to_dot())?;
+        eprintln!("Wrote {}", path.display());
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02"#;
+        assert_eq!("4", Sln::new().part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_part_1_larger_example() {
+        let input = r#"x00: 1
+x01: 0
+x02: 1
+x03: 1
+x04: 0
+y00: 1
+y01: 1
+y02: 1
+y03: 1
+y04: 1
+
+ntg XOR fgs -> mjb
+y02 OR x01 -> tnw
+kwq OR kpj -> z05
+x00 OR x03 -> fst
+tgd XOR rvg -> z01
+vdt OR tnw -> bfw
+bfw AND frj -> z10
+ffh OR nrd -> bqk
+y00 AND y03 -> djm
+y03 OR y00 -> psh
+bqk OR frj -> z08
+tnw OR fst -> frj
+gnj AND tgd -> z11
+bfw XOR mjb -> z00
+x03 OR x00 -> vdt
+gnj AND wpb -> z02
+x04 AND y00 -> kjc
+djm OR pbm -> qhw
+nrd AND vdt -> hwm
+kjc AND fst -> rvg
+y04 OR y02 -> fgs
+y01 AND x02 -> pbm
+ntg OR kjc -> kwq
+psh XOR fgs -> tgd
+qhw XOR tgd -> z09
+pbm OR djm -> kpj
+x03 XOR y03 -> ffh
+x00 XOR y04 -> ntg
+bfw OR bqk -> z06
+nrd XOR fgs -> wpb
+frj XOR qhw -> z04
+bqk OR frj -> z07
+y03 OR x01 -> nrd
+hwm AND bqk -> z03
+tgd XOR rvg -> z12
+tnw OR pbm -> gnj"#;
+        assert_eq!("2024", Sln::new().part_1(input.to_string()));
+    }
+
+    /// A ripple-carry adder for `bits`-bit numbers in the puzzle's format.
+    fn adder(bits: usize) -> String {
+        let mut input = String::new();
+        for prefix in ['x', 'y'] {
+            for i in 0..bits {
+                input += &format!("{}{:02}: 0\n", prefix, i);
+            }
+        }
+        input += "\nx00 XOR y00 -> z00\nx00 AND y00 -> c00\n";
+        for i in 1..bits {
+            let carry_out = if i + 1 == bits {
+                format!("z{:02}", bits)
+            } else {
+                format!("c{:02}", i)
+            };
+            input += &format!("x{i:02} XOR y{i:02} -> s{i:02}\n");
+            input += &format!("c{:02} XOR s{i:02} -> z{i:02}\n", i - 1);
+            input += &format!("y{i:02} AND x{i:02} -> a{i:02}\n");
+            input += &format!("s{i:02} AND c{:02} -> t{i:02}\n", i - 1);
+            input += &format!("a{i:02} OR t{i:02} -> {}\n", carry_out);
+        }
+        input
+    }
+
+    #[test]
+    fn test_adder_is_correct() {
+        let circuit = Circuit::parse(&adder(12));
+        assert!(circuit.adds_correctly());
+        assert!(circuit.misplaced_wires().is_empty());
+        assert_eq!(Some(vec![]), circuit.swapped_pairs());
+    }
+
+    #[test]
+    fn test_part_2() {
+        let mut input = adder(24);
+        for (a, b) in [
+            ("z05", "c05"),
+            ("z10", "a10"),
+            ("s15", "a15"),
+            ("z20", "t20"),
+        ] {
+            input = input
+                .replace(&format!("-> {}\n", a), "-> TMP\n")
+                .replace(&format!("-> {}\n", b), &format!("-> {}\n", a))
+                .replace("-> TMP\n", &format!("-> {}\n", b));
+        }
+        assert!(!Circuit::parse(&input).adds_correctly());
+        assert_eq!("a10,a15,c05,s15,t20,z05,z10,z20", Sln::new().part_2(input));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let circuit = Circuit::parse(&adder(2));
+        let dot = circuit.to_dot();
+        assert!(dot.starts_with("digraph circuit {"));
+        assert!(dot.contains("g0 [label=\"XOR\\nz00\""));
+        assert!(dot.contains("x00 -> g0;"));
+        assert!(dot.contains("g0 -> z00;"));
+        // The carry from bit 0 links gates directly
+        assert!(dot.contains("g1 -> g3;"));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn test_visualize_saves_only_dot() {
+        let dir = std::env::temp_dir().join(format!("aoc-day24-{}", std::process::id()));
+        let mut viz = Visualizer::new(false, Duration::ZERO, Some(dir.clone()));
+        assert!(Sln::new().visualize(adder(2), &mut viz).unwrap());
+
+        let saved = std::fs::read_to_string(dir.join("circuit.dot")).unwrap();
+        assert_eq!(Circuit::parse(&adder(2)).to_dot(), saved);
+        assert_eq!(0, viz.frames());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_visualize_needs_frames_dir() {
+        let mut viz = Visualizer::new(false, Duration::ZERO, None);
+        assert!(Sln::new().visualize(adder(2), &mut viz).is_err());
     }
 }
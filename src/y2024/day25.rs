@@ -1,19 +1,167 @@
-use crate::utils::template::Solution;
+use crate::utils::{parse, template::Solution};
 
 pub struct Sln {}
 
+/// Space in each column between the lock's base and the key's base.
+const SPACE: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Schematic {
+    Lock([u32; 5]),
+    Key([u32; 5]),
+}
+
+impl Schematic {
+    fn parse(block: &str) -> Schematic {
+        let rows: Vec<&str> = block.lines().map(str::trim).collect();
+        let mut heights = [0; 5];
+        for row in &rows[1..rows.len() - 1] {
+            for (h, c) in heights.iter_mut().zip(row.chars()) {
+                if c == '#' {
+                    *h += 1;
+                }
+            }
+        }
+
+        if rows[0].starts_with('#') {
+            Schematic::Lock(heights)
+        } else {
+            Schematic::Key(heights)
+        }
+    }
+
+    /// The filled space as bits, `SPACE + 1` bits per column so neighbouring
+    /// columns never touch. Pins fill a column from the bottom bit up and
+    /// key cuts from the top bit down, so a key fits a lock exactly when
+    /// their masks share no bits.
+    fn mask(&self) -> u32 {
+        let column: u32 = (1 << SPACE) - 1;
+        let (heights, from_top) = match self {
+            Schematic::Lock(heights) => (heights, false),
+            Schematic::Key(heights) => (heights, true),
+        };
+        heights
+            .iter()
+            .enumerate()
+            .map(|(i, &h)| {
+                let bits = if from_top {
+                    column & !(column >> h)
+                } else {
+                    (1 << h) - 1
+                };
+                bits << (i as u32 * (SPACE + 1))
+            })
+            .fold(0, |mask, bits| mask | bits)
+    }
+}
+
 impl Sln {
     pub fn new() -> Sln {
         Sln {}
     }
+
+    fn parse(input: &str) -> (Vec<u32>, Vec<u32>) {
+        let (locks, keys): (Vec<Schematic>, Vec<Schematic>) = parse::sections(input)
+            .into_iter()
+            .filter(|block| !block.trim().is_empty())
+            .map(Schematic::parse)
+            .partition(|s| matches!(s, Schematic::Lock(_)));
+
+        (
+            locks.iter().map(Schematic::mask).collect(),
+            keys.iter().map(Schematic::mask).collect(),
+        )
+    }
 }
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        "".to_string()
+        let (locks, keys) = Self::parse(&input);
+        locks
+            .iter()
+            .map(|lock| keys.iter().filter(|&key| lock & key == 0).count())
+            .sum::<usize>()
+            .to_string()
+    }
+
+    fn part_2(&self, _input: String) -> String {
+        "No second puzzle".to_string()
+    }
+
+    fn has_part_2(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+#####
+##.##
+.#.##
+...##
+...#.
+...#.
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####
+
+.....
+.....
+#.#..
+###..
+###.#
+###.#
+#####
+
+.....
+.....
+.....
+#....
+#.#..
+#.#.#
+#####"#;
+        assert_eq!("3", Sln::new().part_1(input.to_string()));
+    }
+
+    #[test]
+    fn test_parse_heights() {
+        let lock = "#####\n.####\n.####\n.####\n.#.#.\n.#...\n.....";
+        assert_eq!(Schematic::Lock([0, 5, 3, 4, 3]), Schematic::parse(lock));
+        let key = ".....\n#....\n#....\n#...#\n#.#.#\n#.###\n#####";
+        assert_eq!(Schematic::Key([5, 0, 2, 1, 3]), Schematic::parse(key));
+    }
+
+    #[test]
+    fn test_masks_match_heights() {
+        for l in 0..=SPACE {
+            for k in 0..=SPACE {
+                let lock = Schematic::Lock([l, 0, 0, 0, l]).mask();
+                let key = Schematic::Key([k, SPACE, 0, 0, k]).mask();
+                assert_eq!(l + k <= SPACE, lock & key == 0, "lock {} key {}", l, k);
+            }
+        }
     }
 
-    fn part_2(&self, input: String) -> String {
-        "".to_string()
+    #[test]
+    fn test_has_no_part_2() {
+        assert!(!Sln::new().has_part_2());
     }
 }
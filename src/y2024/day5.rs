@@ -1,190 +1,213 @@
-use crate::utils::template::Solution;
-use std::{
-    cmp::Ordering,
-    collections::{HashMap, HashSet},
-};
+use crate::utils::{parse, template::Solution, toposort::PartialOrder};
 
 pub struct Sln {}
 
 type IntType = usize;
 
-struct ForbiddenFollowerMap {
-    map: HashMap<IntType, HashSet<IntType>>,
-}
-
-impl ForbiddenFollowerMap {
-    fn new() -> Self {
-        ForbiddenFollowerMap {
-            map: HashMap::new(),
-        }
+impl Sln {
+    pub fn new() -> Sln {
+        Sln {}
     }
 
-    fn insert(&mut self, key: IntType, forbidden_follower: IntType) {
-        match self.map.get_mut(&key) {
-            Some(sub_map) => {
-                sub_map.insert(forbidden_follower);
-            }
-            None => {
-                self.map
-                    .insert(key, HashSet::from_iter([forbidden_follower]));
-            }
-        };
-    }
+    /// Reads the page ordering rules and the updates.
+    fn parse(input: &str) -> (PartialOrder<IntType>, Vec<Vec<IntType>>) {
+        let sections = parse::sections(input);
+        let rules = parse::records::<(IntType, IntType)>(sections[0], "|").unwrap();
+        let updates = sections[1]
+            .lines()
+            .map(|line| parse::ints(line).unwrap())
+            .collect();
 
-    fn check_forbidden(&self, key: IntType, maybe_forbidden: IntType) -> bool {
-        match self.map.get(&key) {
-            Some(sub_map) => sub_map.contains(&maybe_forbidden),
-            None => false,
-        }
+        (rules.into_iter().collect(), updates)
     }
+}
 
-    fn check_any_forbidden(&self, key: IntType, to_check: &[IntType]) -> bool {
-        match self.map.get(&key) {
-            Some(sub_map) => {
-                for c in to_check {
-                    if sub_map.contains(c) {
-                        return true;
-                    }
-                }
+impl Solution for Sln {
+    fn part_1(&self, input: String) -> String {
+        let (order, updates) = Self::parse(&input);
 
-                false
+        let mut answer = 0;
+        for u in updates {
+            if order.is_consistent(&u) {
+                answer += u[u.len() / 2];
             }
-            None => false,
         }
-    }
-}
 
-impl Sln {
-    pub fn new() -> Sln {
-        Sln {}
+        answer.to_string()
     }
 
-    fn check_ok(forbidden_followers: &ForbiddenFollowerMap, u: &Vec<usize>) -> bool {
-        // let mut forbidden = HashSet::new();
-        for (i, entry) in u.iter().enumerate() {
-            if forbidden_followers.check_any_forbidden(*entry, &u[i..]) {
-                return false;
+    fn part_2(&self, input: String) -> String {
+        let (order, updates) = Self::parse(&input);
+
+        let mut answer = 0;
+        for u in updates {
+            if !order.is_consistent(&u) {
+                let corrected = order.sort(&u).unwrap();
+                answer += corrected[corrected.len() / 2];
             }
         }
 
-        true
+        answer.to_string()
     }
+}
 
-    fn check_ok_ref(rules: &Vec<(usize, usize)>, u: &Vec<&usize>) -> bool {
-        let mut ok = true;
-        for rule in rules {
-            let i1 = u.iter().position(|&x| *x == rule.0).unwrap_or(0);
-            let i2 = u.iter().position(|&x| *x == rule.1).unwrap_or(usize::MAX);
-            if i1 > i2 {
-                ok = false;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
+
+    /// Rules and updates for a random order of up to 12 pages. Every pair of
+    /// pages has a rule, so each update has exactly one correct order.
+    #[derive(Debug, Clone)]
+    struct PrintQueue(String);
+
+    fn shuffle<T>(g: &mut Gen, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, usize::arbitrary(g) % (i + 1));
         }
-        ok
     }
-}
 
-impl Solution for Sln {
-    fn part_1(&self, input: String) -> String {
-        let mut lines = input.lines();
-        let mut forbidden_followers = ForbiddenFollowerMap::new();
-        let mut updates: Vec<Vec<usize>> = Vec::new();
-        let mut proc_rules = true;
-        while let Some(line) = lines.next() {
-            if proc_rules && line == "" {
-                proc_rules = false;
-                continue;
-            }
+    impl Arbitrary for PrintQueue {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let mut order: Vec<IntType> = (10..100).collect();
+            shuffle(g, &mut order);
+            order.truncate(usize::arbitrary(g) % 11 + 2);
 
-            if proc_rules {
-                let mut spl = line.split('|');
-                let u1: usize = spl.next().unwrap().parse::<usize>().unwrap();
-                let u2: usize = spl.next().unwrap().parse::<usize>().unwrap();
-                forbidden_followers.insert(u2, u1);
-            } else {
-                let mut spl = line.split(',');
-                let mut update = Vec::new();
-                for s in spl {
-                    update.push(s.parse().unwrap());
+            let mut rules = vec![];
+            for (i, a) in order.iter().enumerate() {
+                for b in order[i + 1..].iter() {
+                    rules.push(format!("{}|{}", a, b));
                 }
-                updates.push(update);
             }
-        }
-
-        let mut correct_updates = Vec::new();
-        for u in updates {
-            let ok = Self::check_ok(&forbidden_followers, &u);
-
-            if ok {
-                correct_updates.push(u);
+            shuffle(g, &mut rules);
+
+            let mut updates = vec![];
+            for _ in 0..usize::arbitrary(g) % 6 + 1 {
+                let mut pages = order.clone();
+                shuffle(g, &mut pages);
+                pages.truncate((usize::arbitrary(g) % order.len()) | 1);
+                updates.push(
+                    pages
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect::<Vec<_>>()
+                        .join(","),
+                );
             }
-        }
 
-        let mut answer = 0;
-        for u in correct_updates {
-            let c = u.iter().count();
-            answer += u[c / 2];
+            PrintQueue(format!("{}\n\n{}", rules.join("\n"), updates.join("\n")))
         }
-
-        answer.to_string()
     }
 
-    fn part_2(&self, input: String) -> String {
-        let mut lines = input.lines();
-        let mut forbidden_followers = ForbiddenFollowerMap::new();
-        let mut updates: Vec<Vec<usize>> = Vec::new();
-        let mut proc_rules = true;
-        while let Some(line) = lines.next() {
-            if proc_rules && line == "" {
-                proc_rules = false;
+    /// Checks every pair of pages against the rules and fixes updates by
+    /// swapping neighbours until no rule is broken.
+    fn brute_force(input: &str) -> (IntType, IntType) {
+        let (rules, updates) = input.split_once("\n\n").unwrap();
+        let rules: Vec<(IntType, IntType)> = rules
+            .lines()
+            .map(|l| {
+                let (a, b) = l.split_once('|').unwrap();
+                (a.parse().unwrap(), b.parse().unwrap())
+            })
+            .collect();
+        let breaks = |a: IntType, b: IntType| rules.contains(&(b, a));
+
+        let (mut correct, mut corrected) = (0, 0);
+        for line in updates.lines() {
+            let mut u: Vec<IntType> = line.split(',').map(|p| p.parse().unwrap()).collect();
+            let ok = (0..u.len()).all(|i| (i + 1..u.len()).all(|j| !breaks(u[i], u[j])));
+            if ok {
+                correct += u[u.len() / 2];
                 continue;
             }
 
-            if proc_rules {
-                let mut spl = line.split('|');
-                let u1: usize = spl.next().unwrap().parse::<usize>().unwrap();
-                let u2: usize = spl.next().unwrap().parse::<usize>().unwrap();
-                forbidden_followers.insert(u2, u1);
-            } else {
-                let mut spl = line.split(',');
-                let mut update = Vec::new();
-                for s in spl {
-                    update.push(s.parse().unwrap());
+            let mut swapped = true;
+            while swapped {
+                swapped = false;
+                for i in 0..u.len() - 1 {
+                    if breaks(u[i], u[i + 1]) {
+                        u.swap(i, i + 1);
+                        swapped = true;
+                    }
                 }
-                updates.push(update);
-            }
-        }
-
-        let mut incorrect_updates = Vec::new();
-        for u in updates {
-            let ok = Self::check_ok(&forbidden_followers, &u);
-
-            if !ok {
-                incorrect_updates.push(u);
             }
+            corrected += u[u.len() / 2];
         }
 
-        let mut corrected_updates = Vec::new();
-        for u in incorrect_updates {
-            let mut v = u.clone();
-            v.sort_by(|a, b| {
-                if forbidden_followers.check_forbidden(*a, *b) {
-                    Ordering::Greater
-                } else if forbidden_followers.check_forbidden(*b, *a) {
-                    Ordering::Less
-                } else {
-                    a.cmp(b)
-                }
-            });
+        (correct, corrected)
+    }
 
-            corrected_updates.push(v);
-        }
+    #[quickcheck]
+    fn test_matches_brute_force(queue: PrintQueue) {
+        let (part_1, part_2) = brute_force(&queue.0);
+        assert_eq!(part_1.to_string(), Sln::new().part_1(queue.0.clone()));
+        assert_eq!(part_2.to_string(), Sln::new().part_2(queue.0));
+    }
 
-        let mut answer = 0;
-        for u in corrected_updates {
-            let c = u.iter().count();
-            answer += u[c / 2];
-        }
+    #[test]
+    fn test_part_1() {
+        let input = r#"47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47"#;
+        assert_eq!("143", Sln::new().part_1(input.to_string()));
+    }
 
-        answer.to_string()
+    #[test]
+    fn test_part_2() {
+        let input = r#"47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47"#;
+        assert_eq!("123", Sln::new().part_2(input.to_string()));
     }
 }
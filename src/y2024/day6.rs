@@ -1,7 +1,11 @@
+use crate::utils::bitset::{BitGrid, Indexed};
+use crate::utils::sim::{Debugger, Runner, Simulation};
 use crate::utils::template::Solution;
+use crate::utils::viz::Canvas;
+use crate::utils::Point;
 use crate::y2024::day6::Direction::{East, North, South, West};
+use rayon::prelude::*;
 use std::collections::HashSet;
-use std::sync::{Arc, Mutex};
 
 pub struct Sln {}
 
@@ -11,7 +15,7 @@ impl Sln {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 enum Direction {
     North,
     East,
@@ -30,6 +34,14 @@ impl Direction {
     }
 }
 
+impl Indexed for Direction {
+    const COUNT: usize = 4;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
 fn get_next_pos(pos: (usize, usize), direction: &Direction) -> Option<(usize, usize)> {
     Some(match *direction {
         North => (pos.0.checked_sub(1)?, pos.1),
@@ -39,196 +51,195 @@ fn get_next_pos(pos: (usize, usize), direction: &Direction) -> Option<(usize, us
     })
 }
 
-fn check_for_loop(
-    map: &Vec<Vec<char>>,
-    mut guard_pos: (usize, usize),
-    mut guard_direction: Direction,
-) -> bool {
-    let mut visited = HashSet::new();
-    while guard_pos.0 < map.len() && guard_pos.1 < map[guard_pos.0].len() {
-        if guard_pos.0 == 0 && guard_direction == North {
-            return false;
-        } else if guard_pos.1 == 0 && guard_direction == West {
-            return false;
-        }
+/// The guard patrolling the lab, one move per step.
+struct GuardWalk {
+    map: Vec<Vec<char>>,
+    pos: (usize, usize),
+    direction: Direction,
+}
 
-        if !visited.insert((guard_pos, guard_direction)) {
-            return true;
+impl GuardWalk {
+    fn parse(input: &str) -> GuardWalk {
+        let map: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        let mut guard_pos: (usize, usize) = (0, 0);
+        let mut guard_direction = North;
+        for (i, row) in map.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                let direction = match *cell {
+                    '^' => North,
+                    '>' => East,
+                    'v' => South,
+                    '<' => West,
+                    _ => continue,
+                };
+                guard_pos = (i, j);
+                guard_direction = direction;
+            }
         }
 
-        let mut next_pos = match get_next_pos(guard_pos, &guard_direction) {
-            Some(pos) => pos,
-            None => return false,
-        };
-        let mut next_pos_char = match map.get(next_pos.0).and_then(|col| col.get(next_pos.1)) {
-            Some(pos) => *pos,
-            None => return false,
-        };
-        while next_pos_char == '#' {
-            guard_direction = guard_direction.rotate_clockwise();
-            next_pos = match get_next_pos(guard_pos, &guard_direction) {
-                Some(pos) => pos,
-                None => return false,
-            };
-            next_pos_char = match map.get(next_pos.0).and_then(|col| col.get(next_pos.1)) {
-                Some(pos) => *pos,
-                None => return false,
-            };
+        GuardWalk {
+            map,
+            pos: guard_pos,
+            direction: guard_direction,
         }
-
-        guard_pos = next_pos;
     }
 
-    false
+    fn tile(&self, pos: (usize, usize)) -> Option<char> {
+        self.map.get(pos.0).and_then(|row| row.get(pos.1)).copied()
+    }
 }
 
-impl Solution for Sln {
-    fn part_1(&self, input: String) -> String {
-        let mut map: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-        let mut guard_pos: (usize, usize) = (0, 0);
-        let mut guard_direction = North;
-        for (i, row) in map.iter().enumerate() {
-            for (j, cell) in row.iter().enumerate() {
-                if *cell == '^' {
-                    guard_pos = (i, j);
-                    guard_direction = North;
-                    break;
-                } else if *cell == '>' {
-                    guard_pos = (i, j);
-                    guard_direction = East;
-                    break;
-                } else if *cell == 'v' {
-                    guard_pos = (i, j);
-                    guard_direction = South;
-                    break;
-                } else if *cell == '<' {
-                    guard_pos = (i, j);
-                    guard_direction = West;
-                    break;
+impl Simulation for GuardWalk {
+    type Snapshot = ((usize, usize), Direction);
+
+    /// Turns until the way ahead is clear and then moves. Returns `false` once
+    /// the guard would leave the map.
+    fn step(&mut self) -> bool {
+        let mut direction = self.direction;
+        loop {
+            let Some(next_pos) = get_next_pos(self.pos, &direction) else {
+                return false;
+            };
+            match self.tile(next_pos) {
+                None => return false,
+                Some('#') => direction = direction.rotate_clockwise(),
+                Some(_) => {
+                    self.pos = next_pos;
+                    self.direction = direction;
+                    return true;
                 }
             }
         }
+    }
 
-        let mut answer = 1;
-        map[guard_pos.0][guard_pos.1] = 'X';
-        'outer: while guard_pos.0 < map.len() && guard_pos.1 < map[guard_pos.0].len() {
-            if guard_pos.0 == 0 && guard_direction == North {
-                break;
-            } else if guard_pos.1 == 0 && guard_direction == West {
-                break;
-            }
+    fn snapshot(&self) -> Self::Snapshot {
+        (self.pos, self.direction)
+    }
 
-            let mut next_pos = match get_next_pos(guard_pos, &guard_direction) {
-                Some(pos) => pos,
-                None => break,
-            };
-            let mut next_pos_char = match map.get(next_pos.0).and_then(|col| col.get(next_pos.1)) {
-                Some(pos) => *pos,
-                None => break,
-            };
-            while next_pos_char == '#' {
-                guard_direction = guard_direction.rotate_clockwise();
-                next_pos = match get_next_pos(guard_pos, &guard_direction) {
-                    Some(pos) => pos,
-                    None => break 'outer,
-                };
-                next_pos_char = match map.get(next_pos.0).and_then(|col| col.get(next_pos.1)) {
-                    Some(pos) => *pos,
-                    None => break 'outer,
-                };
-            }
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        (self.pos, self.direction) = snapshot;
+    }
 
-            if next_pos_char == '.' {
-                answer += 1;
-                map[next_pos.0][next_pos.1] = 'X';
+    fn canvas(&self) -> Canvas {
+        let mut canvas = Canvas::from_chars(&self.map);
+        for (i, row) in self.map.iter().enumerate() {
+            for (j, &cell) in row.iter().enumerate() {
+                if cell == '#' {
+                    canvas.set(Point::new(j, i), '#', Some([128, 128, 128]));
+                } else if cell != '.' {
+                    canvas.set(Point::new(j, i), '.', None);
+                }
             }
+        }
+        let guard = match self.direction {
+            North => '^',
+            East => '>',
+            South => 'v',
+            West => '<',
+        };
+        canvas.set(
+            Point::new(self.pos.1, self.pos.0),
+            guard,
+            Some([255, 64, 64]),
+        );
+        canvas
+    }
+}
+
+/// Whether the guard walks in a loop from where `walk` starts. `visited` is
+/// cleared first, so the same grid can be reused between checks.
+fn check_for_loop(walk: &mut GuardWalk, visited: &mut BitGrid<Direction>) -> bool {
+    visited.clear();
+    loop {
+        if !visited.insert(Point::new(walk.pos.1, walk.pos.0), walk.direction) {
+            return true;
+        }
+        if !walk.step() {
+            return false;
+        }
+    }
+}
 
-            guard_pos = next_pos;
+impl Solution for Sln {
+    fn part_1(&self, input: String) -> String {
+        let mut walk = GuardWalk::parse(&input);
+        let mut visited = HashSet::from([walk.pos]);
+        while walk.step() {
+            visited.insert(walk.pos);
         }
 
-        answer.to_string()
+        visited.len().to_string()
     }
 
     fn part_2(&self, input: String) -> String {
-        let map: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-        let mut guard_pos: (usize, usize) = (0, 0);
-        let mut guard_direction = North;
-        for (i, row) in map.iter().enumerate() {
-            for (j, cell) in row.iter().enumerate() {
-                if *cell == '^' {
-                    guard_pos = (i, j);
-                    guard_direction = North;
-                    break;
-                } else if *cell == '>' {
-                    guard_pos = (i, j);
-                    guard_direction = East;
-                    break;
-                } else if *cell == 'v' {
-                    guard_pos = (i, j);
-                    guard_direction = South;
-                    break;
-                } else if *cell == '<' {
-                    guard_pos = (i, j);
-                    guard_direction = West;
-                    break;
-                }
+        let mut walk = GuardWalk::parse(&input);
+        let start = walk.snapshot();
+
+        // Only tiles on the guard's original path can change it. This will
+        // correctly ignore the guard starting pos.
+        let mut candidates = vec![];
+        let mut seen = HashSet::new();
+        while walk.step() {
+            if walk.tile(walk.pos) == Some('.') && seen.insert(walk.pos) {
+                candidates.push(walk.pos);
             }
         }
 
-        let guard_starting_pos = guard_pos;
-        let guard_starting_dir = guard_direction;
-        let possible_entries = Arc::new(Mutex::new(HashSet::new()));
-        rayon::scope(|s| {
-            'outer: while guard_pos.0 < map.len() && guard_pos.1 < map[guard_pos.0].len() {
-                if guard_pos.0 == 0 && guard_direction == North {
-                    break;
-                } else if guard_pos.1 == 0 && guard_direction == West {
-                    break;
-                }
-
-                let mut next_pos = match get_next_pos(guard_pos, &guard_direction) {
-                    Some(pos) => pos,
-                    None => break,
-                };
-                let mut next_pos_char =
-                    match map.get(next_pos.0).and_then(|col| col.get(next_pos.1)) {
-                        Some(pos) => *pos,
-                        None => break,
-                    };
-                while next_pos_char == '#' {
-                    guard_direction = guard_direction.rotate_clockwise();
-                    next_pos = match get_next_pos(guard_pos, &guard_direction) {
-                        Some(pos) => pos,
-                        None => break 'outer,
-                    };
-                    next_pos_char = match map.get(next_pos.0).and_then(|col| col.get(next_pos.1)) {
-                        Some(pos) => *pos,
-                        None => break 'outer,
-                    };
-                }
+        let (width, height) = (walk.map[0].len(), walk.map.len());
+        candidates
+            .par_iter()
+            .map_init(
+                || (GuardWalk::parse(&input), BitGrid::new(width, height)),
+                |(walk, visited), &pos| {
+                    // Need to re-run from the beginning, as adding the obstacle might affect previous paths
+                    walk.restore(start);
+                    walk.map[pos.0][pos.1] = '#';
+                    let looped = check_for_loop(walk, visited);
+                    walk.map[pos.0][pos.1] = '.';
+                    looped
+                },
+            )
+            .filter(|&looped| looped)
+            .count()
+            .to_string()
+    }
 
-                // Will correctly ignore guard starting pos.
-                if map[next_pos.0][next_pos.1] == '.'
-                    && !possible_entries.lock().unwrap().contains(&next_pos)
-                {
-                    let mut clone_map = map.clone();
-                    let clone_guard_pos = guard_starting_pos;
-                    let clone_guard_direction = guard_starting_dir;
-                    let possible_entries_clone = possible_entries.clone();
-                    s.spawn(move |_| {
-                        clone_map[next_pos.0][next_pos.1] = '#';
-                        // Need to re-run from the beginning, as adding the obstacle might affect previous paths
-                        if check_for_loop(&clone_map, clone_guard_pos, clone_guard_direction) {
-                            possible_entries_clone.lock().unwrap().insert(next_pos);
-                        }
-                    })
-                }
+    fn step_through(&self, input: String) -> Option<Box<dyn Debugger>> {
+        Some(Box::new(Runner::new(GuardWalk::parse(&input))))
+    }
+}
 
-                guard_pos = next_pos;
-            }
-        });
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_1() {
+        let input = r#"....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#..."#;
+        assert_eq!("41", Sln::new().part_1(input.to_string()));
+    }
 
-        let x = possible_entries.lock().unwrap().len().to_string();
-        x
+    #[test]
+    fn test_part_2() {
+        let input = r#"....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#..."#;
+        assert_eq!("6", Sln::new().part_2(input.to_string()));
     }
 }
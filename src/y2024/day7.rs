@@ -1,11 +1,9 @@
+use aoc_derive::FromPattern;
 use itertools::Itertools;
 use nonempty::NonEmpty;
-use std::{
-    str::FromStr,
-    sync::{atomic::AtomicU64, Arc},
-};
+use std::sync::{atomic::AtomicU64, Arc};
 
-use crate::utils::template::Solution;
+use crate::utils::{num, template::Solution};
 
 pub struct Sln {}
 
@@ -18,58 +16,14 @@ impl Sln {
 type IntType = u64;
 type AtomicIntType = AtomicU64;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, FromPattern)]
+#[aoc(pattern = "{}: {}")]
 struct Equation {
     result: IntType,
+    #[aoc(sep = " ", with = "NonEmpty::from_vec")]
     terms: NonEmpty<IntType>,
 }
 
-#[derive(Debug, PartialEq)]
-enum EquationParseError {
-    TooFewColons,
-    TooManyColons,
-    ResultNotANumber,
-    TermNotANumber,
-    ZeroTerms,
-}
-
-impl FromStr for Equation {
-    type Err = EquationParseError;
-
-    fn from_str(value: &str) -> Result<Self, EquationParseError> {
-        let mut colon_split = value.split(':');
-        let result_part = colon_split.next().unwrap(); // split returns at least one entry
-        let terms_part = colon_split.next().ok_or(EquationParseError::TooFewColons)?;
-
-        if colon_split.next().is_some() {
-            return Err(EquationParseError::TooManyColons);
-        }
-
-        let result = result_part
-            .parse::<IntType>()
-            .map_err(|_| EquationParseError::ResultNotANumber)?;
-
-        let terms_vec = terms_part
-            .trim()
-            .split_whitespace()
-            .filter_map(|t| {
-                if t.is_empty() {
-                    None
-                } else {
-                    Some(
-                        t.parse::<IntType>()
-                            .map_err(|_| EquationParseError::TermNotANumber),
-                    )
-                }
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let terms = NonEmpty::from_vec(terms_vec).ok_or(EquationParseError::ZeroTerms)?;
-
-        Ok(Equation { result, terms })
-    }
-}
-
 enum Operator {
     Add,
     Mul,
@@ -85,49 +39,34 @@ impl Operator {
     /// assert_eq!(Some(123), Operator::Add.apply(100, 23));
     /// ```
     fn apply(&self, val1: IntType, val2: IntType) -> Option<IntType> {
-        Some(match self {
+        match self {
             Operator::Add => val1.checked_add(val2),
             Operator::Mul => val1.checked_mul(val2),
-            Operator::Concat => {
-                let new_digit_count = if val2 == 0 {
-                    1
-                } else {
-                    (val2).ilog10() as IntType + 1
-                };
-                let shift_exponent = (10 as IntType).checked_pow(new_digit_count as u32)?;
-                let shifted_val1 = val1.checked_mul(shift_exponent)?;
-                // println!("{}, {}, {}, {}", val1, *val2, shifted_val1, shifted_val1 + *val2);
-                shifted_val1.checked_add(val2)
-            }
-        }?)
+            Operator::Concat => num::concat_digits(val1, val2),
+        }
     }
 }
 
 impl Sln {
-    fn check_possibly_true(eq: &Equation, operators: &Vec<Operator>) -> bool {
-        for ops in
-            itertools::repeat_n(operators.iter(), eq.terms.len() - 1).multi_cartesian_product()
-        {
-            let mut result = *eq.terms.first();
-            for (term, op) in eq.terms.tail().iter().zip(ops) {
-                result = match op.apply(result, *term) {
-                    Some(r) => r,
-                    None => break,
-                }
-            }
-
-            if result == eq.result {
-                return true;
-            }
-        }
-
-        false
+    /// Whether some combination of `operators`, evaluated left to right,
+    /// turns the terms into the result. Combinations that overflow never do.
+    fn check_possibly_true(eq: &Equation, operators: &[Operator]) -> bool {
+        itertools::repeat_n(operators.iter(), eq.terms.len() - 1)
+            .multi_cartesian_product()
+            .any(|ops| {
+                eq.terms
+                    .tail()
+                    .iter()
+                    .zip(ops)
+                    .try_fold(*eq.terms.first(), |acc, (&term, op)| op.apply(acc, term))
+                    == Some(eq.result)
+            })
     }
 
-    fn sln(&self, input: String, operators: &Vec<Operator>) -> String {
+    fn sln(&self, input: String, operators: &[Operator]) -> String {
         let equations = input
             .lines()
-            .filter(|l| *l != "")
+            .filter(|l| !l.is_empty())
             .map(|l| l.parse::<Equation>())
             .collect::<Result<Vec<_>, _>>()
             .unwrap();
@@ -152,11 +91,11 @@ impl Sln {
 
 impl Solution for Sln {
     fn part_1(&self, input: String) -> String {
-        self.sln(input, &vec![Operator::Add, Operator::Mul])
+        self.sln(input, &[Operator::Add, Operator::Mul])
     }
 
     fn part_2(&self, input: String) -> String {
-        self.sln(input, &vec![Operator::Add, Operator::Mul, Operator::Concat])
+        self.sln(input, &[Operator::Add, Operator::Mul, Operator::Concat])
     }
 }
 
@@ -165,12 +104,73 @@ mod tests {
     use crate::y2024::day7::EquationParseError;
 
     use super::*;
+    use quickcheck::Arbitrary;
+    use quickcheck_macros::quickcheck;
+    use std::collections::HashSet;
+
+    /// Small equations, half of which are made true by construction.
+    impl Arbitrary for Equation {
+        fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+            let len = usize::arbitrary(g) % 6 + 1;
+            let terms: Vec<IntType> = (0..len).map(|_| IntType::arbitrary(g) % 100).collect();
+            let result = if bool::arbitrary(g) {
+                terms[1..].iter().fold(terms[0], |acc, &term| {
+                    let op = g.choose(&[Operator::Add, Operator::Mul, Operator::Concat]);
+                    op.unwrap().apply(acc, term).unwrap()
+                })
+            } else {
+                IntType::arbitrary(g) % 10_000
+            };
+
+            Equation {
+                result,
+                terms: NonEmpty::from_vec(terms).unwrap(),
+            }
+        }
+    }
+
+    /// Every value the operators can make from `terms`, built up one term at
+    /// a time. Values that overflow are dropped.
+    fn reachable(terms: &[IntType], operators: &[Operator]) -> HashSet<IntType> {
+        terms[1..]
+            .iter()
+            .fold(HashSet::from([terms[0]]), |values, &term| {
+                values
+                    .iter()
+                    .flat_map(|&v| operators.iter().filter_map(move |op| op.apply(v, term)))
+                    .collect()
+            })
+    }
+
+    #[quickcheck]
+    fn test_check_possibly_true_matches_reachable(eq: Equation) {
+        let terms: Vec<IntType> = eq.terms.iter().copied().collect();
+        for operators in [
+            vec![Operator::Add, Operator::Mul],
+            vec![Operator::Add, Operator::Mul, Operator::Concat],
+        ] {
+            assert_eq!(
+                reachable(&terms, &operators).contains(&eq.result),
+                Sln::check_possibly_true(&eq, &operators)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_equation() {
+        let eq = "3267: 81 40 27".parse::<Equation>().unwrap();
+        assert_eq!(3267, eq.result);
+        assert_eq!(NonEmpty::from((81, vec![40, 27])), eq.terms);
+    }
 
     #[test]
     fn test_parse_equation_empty_string() {
         assert_eq!(
             "".parse::<Equation>().unwrap_err(),
-            EquationParseError::TooFewColons
+            EquationParseError::ExpectedLiteral {
+                literal: ": ",
+                column: 1
+            }
         );
     }
 
@@ -178,7 +178,11 @@ mod tests {
     fn test_parse_equation_two_results() {
         assert_eq!(
             "123: 234: 45 34".parse::<Equation>().unwrap_err(),
-            EquationParseError::TooManyColons
+            EquationParseError::InvalidField {
+                field: "terms",
+                value: "234:".to_string(),
+                column: 6
+            }
         );
     }
 
@@ -186,7 +190,11 @@ mod tests {
     fn test_parse_equation_zero_terms() {
         assert_eq!(
             "123: ".parse::<Equation>().unwrap_err(),
-            EquationParseError::ZeroTerms
+            EquationParseError::InvalidField {
+                field: "terms",
+                value: "".to_string(),
+                column: 6
+            }
         );
     }
 
@@ -194,7 +202,11 @@ mod tests {
     fn test_parse_equation_non_numeric_result() {
         assert_eq!(
             "1f4wa: 123 234".parse::<Equation>().unwrap_err(),
-            EquationParseError::ResultNotANumber
+            EquationParseError::InvalidField {
+                field: "result",
+                value: "1f4wa".to_string(),
+                column: 1
+            }
         );
     }
 
@@ -202,7 +214,11 @@ mod tests {
     fn test_parse_equation_non_numeric_term() {
         assert_eq!(
             "123: 123 a2bc 234".parse::<Equation>().unwrap_err(),
-            EquationParseError::TermNotANumber
+            EquationParseError::InvalidField {
+                field: "terms",
+                value: "a2bc".to_string(),
+                column: 10
+            }
         );
     }
 
@@ -1,4 +1,6 @@
-use crate::utils::template::Solution;
+use std::ops::Range;
+
+use crate::utils::{ranges::RangeSet, template::Solution};
 
 pub struct Sln {}
 
@@ -53,69 +55,29 @@ impl Solution for Sln {
 
     fn part_2(&self, input: String) -> String {
         let input = input.lines().next().unwrap();
-        let mut hdd = Vec::<i64>::new();
+        let mut files: Vec<Range<i64>> = vec![];
+        let mut pos = 0;
         for (i, c) in input.chars().enumerate() {
+            let len = c.to_digit(10).unwrap() as i64;
             if i % 2 == 0 {
-                for _ in 0..(c.to_digit(10).unwrap()) {
-                    hdd.push(i as i64 / 2);
-                }
-            } else {
-                for _ in 0..(c.to_digit(10).unwrap()) {
-                    hdd.push(-1);
-                }
+                files.push(pos..pos + len);
             }
+            pos += len;
         }
 
-        let mut z = hdd.len() - 1;
-        'outer: while z > 0 {
-            if hdd[z] == -1 {
-                z -= 1;
-                continue;
-            }
-
-            let id = hdd[z];
-            let mut len = 0;
-            while hdd[z] == id {
-                len += 1;
-                if z == 0 {
-                    break 'outer;
-                }
-                z -= 1;
-            }
-
-            let mut a = 0;
-            while a < z {
-                if hdd[a] != -1 {
-                    a += 1;
-                    continue;
-                }
-
-                let mut free_len = 0;
-                let start_a = a;
-                while hdd[a] == -1 {
-                    a += 1;
-                    free_len += 1;
-                }
-
-                if free_len >= len {
-                    for i in (z + 1)..(z + len + 1) {
-                        hdd[i] = -1;
-                    }
-
-                    for i in start_a..(start_a + len) {
-                        hdd[i] = id;
-                    }
-
-                    continue 'outer;
-                }
+        let mut used: RangeSet = files.iter().cloned().collect();
+        for file in files.iter_mut().rev() {
+            let len = file.end - file.start;
+            if let Some(gap) = used.first_gap(len, 0..file.start) {
+                used.remove(file.clone());
+                *file = gap.start..gap.start + len;
+                used.insert(file.clone());
             }
         }
 
         let mut chksum: u64 = 0;
-        for (i, &b) in hdd.iter().enumerate() {
-            if b != -1 {
-                chksum += i as u64 * b as u64;
-            }
+        for (id, file) in files.into_iter().enumerate() {
+            chksum += file.map(|b| b as u64 * id as u64).sum::<u64>();
         }
 
         chksum.to_string()
@@ -125,6 +87,61 @@ impl Solution for Sln {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
+
+    /// Disk map with 1 to 20 files of 1 to 9 blocks and gaps of 0 to 9
+    /// blocks between them.
+    #[derive(Debug, Clone)]
+    struct DiskMap(String);
+
+    impl Arbitrary for DiskMap {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let files = usize::arbitrary(g) % 20 + 1;
+            let mut map = String::new();
+            for i in 0..files {
+                if i > 0 {
+                    map.push(char::from_digit(u32::arbitrary(g) % 10, 10).unwrap());
+                }
+                map.push(char::from_digit(u32::arbitrary(g) % 9 + 1, 10).unwrap());
+            }
+            DiskMap(map)
+        }
+    }
+
+    /// Moves whole files block by block on an expanded disk.
+    fn brute_force_part_2(map: &str) -> u64 {
+        let mut disk: Vec<Option<usize>> = vec![];
+        for (i, c) in map.chars().enumerate() {
+            let id = (i % 2 == 0).then_some(i / 2);
+            disk.extend(std::iter::repeat_n(id, c.to_digit(10).unwrap() as usize));
+        }
+
+        for id in (0..map.len().div_ceil(2)).rev() {
+            let start = disk.iter().position(|&b| b == Some(id)).unwrap();
+            let len = disk[start..].iter().take_while(|&&b| b == Some(id)).count();
+            let gap = (0..start).find(|&s| disk[s..s + len].iter().all(|b| b.is_none()));
+            if let Some(gap) = gap {
+                for i in 0..len {
+                    disk[gap + i] = Some(id);
+                    disk[start + i] = None;
+                }
+            }
+        }
+
+        disk.iter()
+            .enumerate()
+            .map(|(i, b)| b.map_or(0, |id| (i * id) as u64))
+            .sum()
+    }
+
+    #[quickcheck]
+    fn test_part_2_matches_brute_force(map: DiskMap) {
+        assert_eq!(
+            brute_force_part_2(&map.0).to_string(),
+            Sln::new().part_2(map.0)
+        );
+    }
 
     #[test]
     fn test_part_1() {